@@ -0,0 +1,71 @@
+/*
+ * C interface to xotp, available when the crate is built with the `ffi`
+ * feature. See the `ffi` module documentation for details.
+ *
+ * HOTP and TOTP instances are opaque pointers, which must be released with
+ * xotp_hotp_free and xotp_totp_free respectively. Every fallible function
+ * takes an `int *error` out-param (which may be NULL), set to XOTP_OK on
+ * success or one of the XOTP_ERR_* codes on failure. A panic inside the
+ * library is caught and reported as XOTP_ERR_PANIC.
+ */
+
+#ifndef XOTP_H
+#define XOTP_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define XOTP_OK 0
+#define XOTP_ERR_NULL_POINTER 1
+#define XOTP_ERR_INVALID_UTF8 2
+#define XOTP_ERR_INVALID_URI 3
+#define XOTP_ERR_INVALID_SECRET 4
+#define XOTP_ERR_INVALID_ALGORITHM 5
+#define XOTP_ERR_INVALID_DIGITS 6
+#define XOTP_ERR_INVALID_PERIOD 7
+#define XOTP_ERR_INVALID_COUNTER 8
+#define XOTP_ERR_PANIC 9
+
+#define XOTP_KIND_TOTP 0
+#define XOTP_KIND_HOTP 1
+
+#define XOTP_DIGEST_SHA1 0
+#define XOTP_DIGEST_SHA256 1
+#define XOTP_DIGEST_SHA512 2
+#define XOTP_DIGEST_SHA224 3
+#define XOTP_DIGEST_SHA384 4
+/* The SHA3 digests are only accepted when built with the `sha3` feature */
+#define XOTP_DIGEST_SHA3_256 5
+#define XOTP_DIGEST_SHA3_512 6
+
+typedef struct XotpTotp XotpTotp;
+typedef struct XotpHotp XotpHotp;
+
+typedef struct XotpParseResult {
+    int kind;
+    XotpTotp *totp;
+    XotpHotp *hotp;
+    uint64_t counter;
+} XotpParseResult;
+
+XotpTotp *xotp_totp_new(const uint8_t *secret, size_t secret_len, int digest,
+                        uint32_t digits, uint64_t period, int *error);
+uint32_t xotp_totp_get_otp(const XotpTotp *totp, uint64_t time, int *error);
+void xotp_totp_free(XotpTotp *totp);
+
+XotpHotp *xotp_hotp_new(const uint8_t *secret, size_t secret_len,
+                        uint32_t digits, int *error);
+uint32_t xotp_hotp_get_otp(const XotpHotp *hotp, uint64_t counter, int *error);
+void xotp_hotp_free(XotpHotp *hotp);
+
+void xotp_parse_uri(const char *uri, XotpParseResult *out, int *error);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* XOTP_H */
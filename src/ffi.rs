@@ -0,0 +1,351 @@
+// A C-compatible interface to the HOTP and TOTP structs
+
+//! A C-compatible interface, enabled with the `ffi` feature.
+//!
+//! [`HOTP`] and [`TOTP`] instances are passed across the boundary as opaque
+//! pointers, which are created by the `xotp_*_new` functions and
+//! [`xotp_parse_uri`], and must be released with the matching `xotp_*_free`
+//! function.
+//!
+//! Rather than returning Rust enums, every fallible function takes an `int`
+//! out-param for its error code, set to [`XOTP_OK`] on success or one of the
+//! other `XOTP_ERR_*` constants on failure. The out-param may be null if the
+//! caller isn't interested in the error.
+//!
+//! Panics are caught before they can unwind into the caller, which is
+//! undefined behavior across an `extern "C"` boundary. A function that
+//! panics sets [`XOTP_ERR_PANIC`] and returns the same value as on failure.
+//!
+//! A C header declaring this interface is included with the crate at
+//! `include/xotp.h`, which the `ffi` tests check against this module.
+
+use crate::hotp::HOTP;
+use crate::totp::TOTP;
+use crate::util::{parse_otpauth_uri, MacDigest, ParseError, ParseResult};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// The call succeeded.
+pub const XOTP_OK: c_int = 0;
+/// A required pointer argument was null.
+pub const XOTP_ERR_NULL_POINTER: c_int = 1;
+/// The given string wasn't valid UTF-8.
+pub const XOTP_ERR_INVALID_UTF8: c_int = 2;
+/// The URI couldn't be parsed, wasn't an otpauth URI or had an unknown type.
+pub const XOTP_ERR_INVALID_URI: c_int = 3;
+/// The secret was missing or couldn't be decoded.
+pub const XOTP_ERR_INVALID_SECRET: c_int = 4;
+/// The digest algorithm wasn't recognized.
+pub const XOTP_ERR_INVALID_ALGORITHM: c_int = 5;
+/// The digit count wasn't between 1 and 10.
+pub const XOTP_ERR_INVALID_DIGITS: c_int = 6;
+/// The period was invalid.
+pub const XOTP_ERR_INVALID_PERIOD: c_int = 7;
+/// The HOTP counter was missing or invalid.
+pub const XOTP_ERR_INVALID_COUNTER: c_int = 8;
+/// The call panicked, which was caught rather than unwinding into C.
+pub const XOTP_ERR_PANIC: c_int = 9;
+
+/// The `kind` of an [`XotpParseResult`] holding a TOTP instance.
+pub const XOTP_KIND_TOTP: c_int = 0;
+/// The `kind` of an [`XotpParseResult`] holding an HOTP instance.
+pub const XOTP_KIND_HOTP: c_int = 1;
+
+/// The `digest` passed to [`xotp_totp_new`] to use [`MacDigest::SHA1`].
+pub const XOTP_DIGEST_SHA1: c_int = 0;
+/// The `digest` passed to [`xotp_totp_new`] to use [`MacDigest::SHA256`].
+pub const XOTP_DIGEST_SHA256: c_int = 1;
+/// The `digest` passed to [`xotp_totp_new`] to use [`MacDigest::SHA512`].
+pub const XOTP_DIGEST_SHA512: c_int = 2;
+/// The `digest` passed to [`xotp_totp_new`] to use [`MacDigest::SHA224`].
+pub const XOTP_DIGEST_SHA224: c_int = 3;
+/// The `digest` passed to [`xotp_totp_new`] to use [`MacDigest::SHA384`].
+pub const XOTP_DIGEST_SHA384: c_int = 4;
+/// The `digest` passed to [`xotp_totp_new`] to use SHA3-256, only accepted
+/// when the crate is built with the `sha3` feature.
+pub const XOTP_DIGEST_SHA3_256: c_int = 5;
+/// The `digest` passed to [`xotp_totp_new`] to use SHA3-512, only accepted
+/// when the crate is built with the `sha3` feature.
+pub const XOTP_DIGEST_SHA3_512: c_int = 6;
+
+/// The result of [`xotp_parse_uri`], in place of the [`ParseResult`] enum.
+///
+/// Depending on `kind`, either `totp` or `hotp` (along with `counter`) is
+/// set, while the other pointer is null.
+#[repr(C)]
+pub struct XotpParseResult {
+    pub kind: c_int,
+    pub totp: *mut TOTP,
+    pub hotp: *mut HOTP,
+    pub counter: u64,
+}
+
+/// Maps a [`ParseError`] to its C error code.
+fn error_code(error: &ParseError) -> c_int {
+    match error {
+        ParseError::UriParseError(_)
+        | ParseError::WrongScheme(_)
+        | ParseError::MissingOtpType
+        | ParseError::UnknownOtpType(_)
+        | ParseError::IssuerMismatch(_, _)
+        | ParseError::MissingMigrationData
+        | ParseError::InvalidMigrationData(_)
+        | ParseError::InvalidTimeStart(_)
+        | ParseError::InvalidBytes => XOTP_ERR_INVALID_URI,
+        ParseError::MissingSecret
+        | ParseError::SecretParsingError(_)
+        | ParseError::SecretTooShort(_)
+        | ParseError::EmptySecret => XOTP_ERR_INVALID_SECRET,
+        ParseError::UnknownAlgorithm(_) | ParseError::UnsupportedEncoding(_) => {
+            XOTP_ERR_INVALID_ALGORITHM
+        }
+        ParseError::WrongDigitNumber { .. } => XOTP_ERR_INVALID_DIGITS,
+        ParseError::InvalidPeriod { .. } => XOTP_ERR_INVALID_PERIOD,
+        ParseError::MissingCounter | ParseError::WrongCounter(_) => XOTP_ERR_INVALID_COUNTER,
+    }
+}
+
+/// Writes an error code to the out-param, if it isn't null.
+unsafe fn set_error(error: *mut c_int, code: c_int) {
+    if !error.is_null() {
+        *error = code;
+    }
+}
+
+/// Runs the body of an exported function, catching any panic so it doesn't
+/// unwind across the FFI boundary.
+///
+/// On a panic, [`XOTP_ERR_PANIC`] is written to `error` and `on_panic` is
+/// returned instead.
+unsafe fn catch_panic<T>(error: *mut c_int, on_panic: T, body: impl FnOnce() -> T) -> T {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(_) => {
+            set_error(error, XOTP_ERR_PANIC);
+            on_panic
+        }
+    }
+}
+
+/// Reads a secret from a pointer and length, treating a null pointer as an
+/// empty secret only if the length is 0.
+unsafe fn read_secret<'a>(secret: *const u8, secret_len: usize) -> Option<&'a [u8]> {
+    if secret.is_null() {
+        return if secret_len == 0 { Some(&[]) } else { None };
+    }
+    Some(std::slice::from_raw_parts(secret, secret_len))
+}
+
+/// Creates a TOTP instance, validating the digit count and period.
+///
+/// `digest` is one of the `XOTP_DIGEST_*` constants. Returns null on failure.
+///
+/// # Safety
+/// `secret` must point to `secret_len` readable bytes, and `error` must be
+/// null or point to a writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_totp_new(
+    secret: *const u8,
+    secret_len: usize,
+    digest: c_int,
+    digits: u32,
+    period: u64,
+    error: *mut c_int,
+) -> *mut TOTP {
+    catch_panic(error, ptr::null_mut(), || {
+        let secret = match read_secret(secret, secret_len) {
+            Some(secret) => secret,
+            None => {
+                set_error(error, XOTP_ERR_NULL_POINTER);
+                return ptr::null_mut();
+            }
+        };
+        let mac_digest = match digest {
+            XOTP_DIGEST_SHA1 => MacDigest::SHA1,
+            XOTP_DIGEST_SHA256 => MacDigest::SHA256,
+            XOTP_DIGEST_SHA512 => MacDigest::SHA512,
+            XOTP_DIGEST_SHA224 => MacDigest::SHA224,
+            XOTP_DIGEST_SHA384 => MacDigest::SHA384,
+            #[cfg(feature = "sha3")]
+            XOTP_DIGEST_SHA3_256 => MacDigest::SHA3_256,
+            #[cfg(feature = "sha3")]
+            XOTP_DIGEST_SHA3_512 => MacDigest::SHA3_512,
+            _ => {
+                set_error(error, XOTP_ERR_INVALID_ALGORITHM);
+                return ptr::null_mut();
+            }
+        };
+        match TOTP::try_new(secret, mac_digest, digits, period) {
+            Ok(totp) => {
+                set_error(error, XOTP_OK);
+                Box::into_raw(Box::new(totp))
+            }
+            Err(e) => {
+                set_error(error, error_code(&e));
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Generates the TOTP code for the given time in seconds.
+///
+/// Returns 0 and sets [`XOTP_ERR_NULL_POINTER`] if `totp` is null.
+///
+/// # Safety
+/// `totp` must be null or a pointer returned by this library that hasn't
+/// been freed, and `error` must be null or point to a writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_totp_get_otp(totp: *const TOTP, time: u64, error: *mut c_int) -> u32 {
+    catch_panic(error, 0, || match totp.as_ref() {
+        Some(totp) => {
+            set_error(error, XOTP_OK);
+            totp.get_otp(time).as_u32()
+        }
+        None => {
+            set_error(error, XOTP_ERR_NULL_POINTER);
+            0
+        }
+    })
+}
+
+/// Frees a TOTP instance. Does nothing if `totp` is null.
+///
+/// # Safety
+/// `totp` must be null or a pointer returned by this library that hasn't
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_totp_free(totp: *mut TOTP) {
+    catch_panic(ptr::null_mut(), (), || {
+        if !totp.is_null() {
+            drop(Box::from_raw(totp));
+        }
+    })
+}
+
+/// Creates an HOTP instance, validating the digit count.
+///
+/// Returns null on failure.
+///
+/// # Safety
+/// `secret` must point to `secret_len` readable bytes, and `error` must be
+/// null or point to a writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_hotp_new(
+    secret: *const u8,
+    secret_len: usize,
+    digits: u32,
+    error: *mut c_int,
+) -> *mut HOTP {
+    catch_panic(error, ptr::null_mut(), || {
+        let secret = match read_secret(secret, secret_len) {
+            Some(secret) => secret,
+            None => {
+                set_error(error, XOTP_ERR_NULL_POINTER);
+                return ptr::null_mut();
+            }
+        };
+        match HOTP::try_new(secret, digits) {
+            Ok(hotp) => {
+                set_error(error, XOTP_OK);
+                Box::into_raw(Box::new(hotp))
+            }
+            Err(e) => {
+                set_error(error, error_code(&e));
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Generates the HOTP code for the given counter.
+///
+/// Returns 0 and sets [`XOTP_ERR_NULL_POINTER`] if `hotp` is null.
+///
+/// # Safety
+/// `hotp` must be null or a pointer returned by this library that hasn't
+/// been freed, and `error` must be null or point to a writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_hotp_get_otp(
+    hotp: *const HOTP,
+    counter: u64,
+    error: *mut c_int,
+) -> u32 {
+    catch_panic(error, 0, || match hotp.as_ref() {
+        Some(hotp) => {
+            set_error(error, XOTP_OK);
+            hotp.get_otp(counter).as_u32()
+        }
+        None => {
+            set_error(error, XOTP_ERR_NULL_POINTER);
+            0
+        }
+    })
+}
+
+/// Frees an HOTP instance. Does nothing if `hotp` is null.
+///
+/// # Safety
+/// `hotp` must be null or a pointer returned by this library that hasn't
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_hotp_free(hotp: *mut HOTP) {
+    catch_panic(ptr::null_mut(), (), || {
+        if !hotp.is_null() {
+            drop(Box::from_raw(hotp));
+        }
+    })
+}
+
+/// Parses a null-terminated otpauth URI into `out`.
+///
+/// On success, the instance in `out` must be freed with [`xotp_totp_free`]
+/// or [`xotp_hotp_free`] depending on its `kind`. On failure, `out` is left
+/// untouched.
+///
+/// # Safety
+/// `uri` must point to a null-terminated string, `out` must point to a
+/// writable [`XotpParseResult`], and `error` must be null or point to a
+/// writable `int`.
+#[no_mangle]
+pub unsafe extern "C" fn xotp_parse_uri(
+    uri: *const c_char,
+    out: *mut XotpParseResult,
+    error: *mut c_int,
+) {
+    catch_panic(error, (), || {
+        if uri.is_null() || out.is_null() {
+            set_error(error, XOTP_ERR_NULL_POINTER);
+            return;
+        }
+        let uri = match CStr::from_ptr(uri).to_str() {
+            Ok(uri) => uri,
+            Err(_) => {
+                set_error(error, XOTP_ERR_INVALID_UTF8);
+                return;
+            }
+        };
+        let result = match parse_otpauth_uri(uri) {
+            Ok(ParseResult::TOTP(totp)) => XotpParseResult {
+                kind: XOTP_KIND_TOTP,
+                totp: Box::into_raw(Box::new(totp)),
+                hotp: ptr::null_mut(),
+                counter: 0,
+            },
+            Ok(ParseResult::HOTP(hotp, counter)) => XotpParseResult {
+                kind: XOTP_KIND_HOTP,
+                totp: ptr::null_mut(),
+                hotp: Box::into_raw(Box::new(hotp)),
+                counter,
+            },
+            Err(e) => {
+                set_error(error, error_code(&e));
+                return;
+            }
+        };
+        out.write(result);
+        set_error(error, XOTP_OK);
+    })
+}
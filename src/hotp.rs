@@ -1,7 +1,33 @@
 // Implementation of the HOTP standard according to RFC4226 by Tejas Mehta
 
 use crate::otp_result::OTPResult;
-use crate::util::{base32_decode, get_code, hash_generic, MacDigest};
+#[cfg(feature = "arbitrary")]
+use crate::util::arbitrary_secret;
+#[cfg(feature = "std")]
+use crate::util::{base32_decode_with_alphabet, base32_encode, build_otpauth_uri, Base32Alphabet};
+#[cfg(feature = "base64")]
+use crate::util::{base64_decode, base64_encode};
+use crate::util::{
+    check_secret_len, check_secret_not_empty, digits_byte, dynamic_truncate, get_code,
+    hash_generic, hash_internal, hex_decode, hex_encode, truncate_hash, write_secret, ByteReader,
+    KeyedHmac, MacDigest, OtpError, ParseError, RedactedSecret, ValueError, BYTES_VERSION,
+    DIGITS_RANGE,
+};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+use core::ops::Range;
+use hmac::digest::KeyInit;
+use hmac::Mac;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The digit count used when none is given, as recommended by [RFC4226].
+///
+/// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226
+pub const DEFAULT_DIGITS: u32 = 6;
 
 /// A HOTP Generator
 ///
@@ -16,20 +42,48 @@ use crate::util::{base32_decode, get_code, hash_generic, MacDigest};
 /// In addition to the example, all other initialization methods can be
 /// utilized in a similar manner.
 ///
+/// Equality and hashing both cover every field, including the secret, so
+/// instances can be used as `HashMap` or `HashSet` keys.
+///
 /// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226
 
-#[derive(Debug, Clone, Hash)]
+#[derive(Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HOTP {
     /// The secret key used in the HMAC process.
     ///
     /// Often given as a Base32 key, which can be conveniently initialized
     /// using the [`HOTP::default_from_base32`] constructor.
+    ///
+    /// When serialized, the secret is base32-encoded.
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::base32_serde"))]
     secret: Vec<u8>,
 
     /// The number of digits of the code generated.
     ///
-    /// This value defaults to 6 if not specified in a constructor.
+    /// This value defaults to [`DEFAULT_DIGITS`] if not specified in a
+    /// constructor.
     digits: u32,
+
+    /// The issuer of the account the HOTP belongs to, if known.
+    ///
+    /// This value is set when parsed from an otpauth URI.
+    #[cfg_attr(feature = "serde", serde(default))]
+    issuer: Option<String>,
+
+    /// The name of the account the HOTP belongs to, if known.
+    ///
+    /// This value is set when parsed from an otpauth URI.
+    #[cfg_attr(feature = "serde", serde(default))]
+    account: Option<String>,
+
+    /// Query parameters of the otpauth URI the HOTP was parsed from that
+    /// aren't otherwise recognized, such as a provider's `image`.
+    ///
+    /// These are included again by [`HOTP::to_uri`], so vendor extensions
+    /// survive an import and export.
+    #[cfg_attr(feature = "serde", serde(default))]
+    extras: BTreeMap<String, String>,
 }
 
 /// All initializer implementations for the [`HOTP`] struct.
@@ -39,11 +93,64 @@ impl HOTP {
     ///
     /// Since only SHA1 was specified in the reference implementation and
     /// RFC specification, there's no need to initialize with a digest object.
+    ///
+    /// No validation is done on the secret or digit count. An empty secret
+    /// generates codes that look valid but are meaningless, and a count of 0
+    /// generates a code of 0 every time. Use [`HOTP::try_new`] to reject
+    /// these values.
     pub fn new(secret: &[u8], digits: u32) -> Self {
+        HOTP::from_secret_vec(secret.to_vec(), digits)
+    }
+
+    /// Creates a new HOTP instance from an owned byte vector of the secret
+    /// and specified digit count.
+    ///
+    /// Unlike [`HOTP::new`], the vector is moved into the instance rather
+    /// than copied, so no other copy of the secret is left in memory. Just
+    /// like [`HOTP::new`], no validation is done on the secret or digit
+    /// count, so an empty vector generates codes that look valid but are
+    /// meaningless. Use [`HOTP::try_new`] to reject an empty secret.
+    pub fn from_secret_vec(secret: Vec<u8>, digits: u32) -> Self {
         HOTP {
-            secret: secret.to_vec(),
+            secret,
             digits,
+            issuer: None,
+            account: None,
+            extras: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a new HOTP instance with a byte-array representation
+    /// of the secret and specified digit count, validating the secret and
+    /// digit count.
+    ///
+    /// Returns a [`ParseError::EmptySecret`] if the secret is empty, or a
+    /// [`ParseError::WrongDigitNumber`] if the digit count isn't between 1
+    /// and 10.
+    pub fn try_new(secret: &[u8], digits: u32) -> Result<Self, ParseError> {
+        check_secret_not_empty(secret)?;
+        if !DIGITS_RANGE.contains(&digits) {
+            return Err(ParseError::WrongDigitNumber {
+                value: digits.to_string(),
+                reason: ValueError::OutOfRange,
+            });
         }
+        Ok(HOTP::new(secret, digits))
+    }
+
+    /// Creates a new HOTP instance just like [`HOTP::try_new`], but also
+    /// rejects secrets shorter than [`MIN_SECRET_BYTES`].
+    ///
+    /// Returns a [`ParseError::SecretTooShort`] with the secret's length if
+    /// it's too short, on top of the errors returned by [`HOTP::try_new`].
+    /// Use this over the other constructors when handling secrets from
+    /// untrusted sources.
+    ///
+    /// [`MIN_SECRET_BYTES`]: crate::util::MIN_SECRET_BYTES
+    pub fn try_new_checked(secret: &[u8], digits: u32) -> Result<Self, ParseError> {
+        let hotp = HOTP::try_new(secret, digits)?;
+        check_secret_len(secret)?;
+        Ok(hotp)
     }
 
     /// Creates a new HOTP instance from a utf8-encoded string secret
@@ -57,22 +164,33 @@ impl HOTP {
     ///
     /// # Panics
     /// This method panics if the provided string is not correctly
-    /// base32-encoded.
+    /// base32-encoded. Use [`HOTP::try_from_base32`] to handle invalid input
+    /// instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
     pub fn new_from_base32(secret: &str, digits: u32) -> Self {
-        let decoded = base32_decode(secret).expect("Failed to decode base32 string");
-        HOTP::new(&decoded, digits)
+        HOTP::try_from_base32(secret, digits).expect("Failed to decode base32 string")
+    }
+
+    /// Creates a new HOTP instance from a byte-array representation of
+    /// the secret and the digit count of a [`HotpConfig`].
+    ///
+    /// Just like [`HOTP::new`], no validation is done on the secret or digit
+    /// count.
+    pub fn from_config(secret: &[u8], config: &HotpConfig) -> Self {
+        HOTP::new(secret, config.digits)
     }
 
     /// Creates a new HOTP instance from a byte-array representation of
     /// the secret and a default digit count of 6.
     pub fn default_from_secret(secret: &[u8]) -> Self {
-        HOTP::new(secret, 6)
+        HOTP::from_config(secret, &HotpConfig::default())
     }
 
     /// Creates a new HOTP instance from an utf8-encoded string secret
     /// and a default digit count of 6..
     pub fn default_from_utf8(secret: &str) -> Self {
-        HOTP::new_from_utf8(secret, 6)
+        HOTP::default_from_secret(secret.as_bytes())
     }
 
     /// Creates a new HOTP instance from a base32-encoded string secret
@@ -80,9 +198,160 @@ impl HOTP {
     ///
     /// # Panics
     /// This method panics if the provided string is not correctly
-    /// base32-encoded.
+    /// base32-encoded. Use [`HOTP::try_default_from_base32`] to handle
+    /// invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
     pub fn default_from_base32(secret: &str) -> Self {
-        HOTP::new_from_base32(secret, 6)
+        HOTP::new_from_base32(secret, DEFAULT_DIGITS)
+    }
+
+    /// Creates a new HOTP instance from a base32-encoded string secret
+    /// and specified digit count.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly base32-encoded, or a [`ParseError::EmptySecret`] if
+    /// it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_from_base32(secret: &str, digits: u32) -> Result<Self, ParseError> {
+        HOTP::try_from_base32_with_alphabet(secret, Base32Alphabet::Rfc4648, digits)
+    }
+
+    /// Creates a new HOTP instance from a string secret encoded with the
+    /// given base32 alphabet and specified digit count.
+    ///
+    /// # Panics
+    /// This method panics if the provided string is not correctly encoded
+    /// with the alphabet. Use [`HOTP::try_from_base32_with_alphabet`] to
+    /// handle invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn new_from_base32_with_alphabet(
+        secret: &str,
+        alphabet: Base32Alphabet,
+        digits: u32,
+    ) -> Self {
+        HOTP::try_from_base32_with_alphabet(secret, alphabet, digits)
+            .expect("Failed to decode base32 string")
+    }
+
+    /// Creates a new HOTP instance from a string secret encoded with the
+    /// given base32 alphabet and specified digit count.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly encoded with the alphabet, or a
+    /// [`ParseError::EmptySecret`] if it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_from_base32_with_alphabet(
+        secret: &str,
+        alphabet: Base32Alphabet,
+        digits: u32,
+    ) -> Result<Self, ParseError> {
+        match base32_decode_with_alphabet(secret, alphabet) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(HOTP::from_secret_vec(decoded, digits))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new HOTP instance from a base32-encoded string secret
+    /// and a default digit count of 6.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly base32-encoded, or a [`ParseError::EmptySecret`] if
+    /// it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_default_from_base32(secret: &str) -> Result<Self, ParseError> {
+        HOTP::try_from_base32(secret, DEFAULT_DIGITS)
+    }
+
+    /// Creates a new HOTP instance from a hex-encoded string secret
+    /// and specified digit count.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// has an odd length or contains non-hex characters, or a
+    /// [`ParseError::EmptySecret`] if it's empty.
+    pub fn new_from_hex(secret: &str, digits: u32) -> Result<Self, ParseError> {
+        match hex_decode(secret) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(HOTP::from_secret_vec(decoded, digits))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new HOTP instance from a hex-encoded string secret
+    /// and a default digit count of 6.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// has an odd length or contains non-hex characters, or a
+    /// [`ParseError::EmptySecret`] if it's empty.
+    pub fn default_from_hex(secret: &str) -> Result<Self, ParseError> {
+        HOTP::new_from_hex(secret, DEFAULT_DIGITS)
+    }
+
+    /// Creates a new HOTP instance from a base64-encoded string secret
+    /// and specified digit count.
+    ///
+    /// Both the standard and URL-safe alphabets are accepted, with or without
+    /// padding. Returns a [`ParseError::SecretParsingError`] if the provided
+    /// string isn't correctly base64-encoded, or a [`ParseError::EmptySecret`]
+    /// if it decodes to an empty secret. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn new_from_base64(secret: &str, digits: u32) -> Result<Self, ParseError> {
+        match base64_decode(secret) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(HOTP::from_secret_vec(decoded, digits))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new HOTP instance from a base64-encoded string secret
+    /// and a default digit count of 6.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// isn't correctly base64-encoded, or a [`ParseError::EmptySecret`] if it
+    /// decodes to an empty secret. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn default_from_base64(secret: &str) -> Result<Self, ParseError> {
+        HOTP::new_from_base64(secret, DEFAULT_DIGITS)
+    }
+
+    /// Sets the issuer and account name the HOTP belongs to.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_label(mut self, issuer: Option<String>, account: Option<String>) -> Self {
+        self.issuer = issuer;
+        self.account = account;
+        self
+    }
+
+    /// Sets the unrecognized query parameters of the URI the HOTP was
+    /// parsed from.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_extras(mut self, extras: BTreeMap<String, String>) -> Self {
+        self.extras = extras;
+        self
+    }
+
+    /// Sets the issuer of the account the HOTP belongs to.
+    ///
+    /// The issuer defaults to `None` with all other constructors.
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(String::from(issuer));
+        self
+    }
+
+    /// Sets the name of the account the HOTP belongs to.
+    ///
+    /// The account name defaults to `None` with all other constructors.
+    pub fn with_account(mut self, account: &str) -> Self {
+        self.account = Some(String::from(account));
+        self
     }
 }
 
@@ -92,6 +361,46 @@ impl HOTP {
     pub fn get_digits(&self) -> u32 {
         self.digits
     }
+
+    /// Gets the raw bytes of the secret.
+    pub fn get_secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Gets the secret as a base32 string, using the same RFC4648 alphabet
+    /// (without padding) the base32 constructors decode.
+    #[cfg(feature = "std")]
+    pub fn get_secret_base32(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// Gets the secret as a lowercase hex string.
+    pub fn get_secret_hex(&self) -> String {
+        hex_encode(&self.secret)
+    }
+
+    /// Gets the secret as a padded base64 string, using the standard
+    /// alphabet. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn get_secret_base64(&self) -> String {
+        base64_encode(&self.secret)
+    }
+
+    /// Gets the issuer of the account the HOTP belongs to, if known.
+    pub fn get_issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    /// Gets the name of the account the HOTP belongs to, if known.
+    pub fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// Gets the unrecognized query parameters of the otpauth URI the HOTP
+    /// was parsed from, which is empty for all other instances.
+    pub fn get_extras(&self) -> &BTreeMap<String, String> {
+        &self.extras
+    }
 }
 
 /// All otp generation methods for the [`HOTP`] struct.
@@ -101,15 +410,385 @@ impl HOTP {
     /// Uses the given counter value.
     ///
     /// # Panics
-    /// This method panics if the hash's secret is incorrectly given.
+    /// This method panics if the hash's secret is incorrectly given. Use
+    /// [`HOTP::try_get_otp`] to get an error instead.
     pub fn get_otp(&self, counter: u64) -> OTPResult {
         let hash = hash_generic(&counter.to_be_bytes(), &self.secret, &MacDigest::SHA1);
-        let offset = (hash[hash.len() - 1] & 0xf) as usize;
-        let bytes: [u8; 4] = hash[offset..offset + 4]
-            .try_into()
-            .expect("Failed byte get");
+        self.result_from_hash(&hash).with_counter(counter)
+    }
+
+    /// Generates and returns the HOTP value for the given counter, just like
+    /// [`HOTP::get_otp`], but returns an error rather than panicking.
+    ///
+    /// Returns an [`OtpError::InvalidKey`] if the secret can't be used to key
+    /// the HMAC.
+    pub fn try_get_otp(&self, counter: u64) -> Result<OTPResult, OtpError> {
+        let hash = KeyedHmac::try_new(&self.secret, &MacDigest::SHA1)?.hash(&counter.to_be_bytes());
+        Ok(self.result_from_hash(&hash).with_counter(counter))
+    }
+
+    /// Returns an iterator over the codes generated for a range of counters.
+    ///
+    /// Each counter in the range is yielded along with its code, as returned
+    /// by [`HOTP::get_otp`]. An empty range yields nothing.
+    ///
+    /// # Panics
+    /// Iterating panics if the hash's secret is incorrectly given.
+    pub fn codes_for_counters(
+        &self,
+        range: Range<u64>,
+    ) -> impl Iterator<Item = (u64, OTPResult)> + '_ {
+        range.map(move |counter| (counter, self.get_otp(counter)))
+    }
+
+    /// Generates and returns the HOTP value using a caller-supplied HMAC
+    /// type instead of SHA1.
+    ///
+    /// This allows any [`Mac`] implementation to be used, such as
+    /// `Hmac<Ripemd160>` for legacy tokens. Uses the given counter value.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given, or if
+    /// the HMAC output is shorter than the 20 bytes needed for truncation.
+    pub fn get_otp_generic<D: Mac + KeyInit>(&self, counter: u64) -> OTPResult {
+        let hash = hash_internal::<D>(&counter.to_be_bytes(), &self.secret);
+        self.result_from_hash(&hash).with_counter(counter)
+    }
+
+    /// Returns the full 20-byte HMAC-SHA1 output for the specified counter,
+    /// before it's truncated into a code.
+    ///
+    /// This is meant for comparing against test vectors or other
+    /// implementations when diagnosing why they disagree on a code.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn hmac_for_counter(&self, counter: u64) -> Vec<u8> {
+        hash_generic(&counter.to_be_bytes(), &self.secret, &MacDigest::SHA1).to_vec()
+    }
+
+    /// Generates and returns the HOTP value for the specified counter,
+    /// optionally truncating the HMAC at a fixed offset.
+    ///
+    /// With `None`, the dynamic offset from the RFC is used and the code is
+    /// the same as the one given by [`HOTP::get_otp`]. With `Some(n)`, the
+    /// four bytes starting at `n` are used instead, as some legacy tokens
+    /// do. Codes generated with a fixed offset won't match those of any
+    /// standard authenticator or verifier.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given, or if
+    /// the offset is above 16.
+    pub fn get_otp_with_offset(&self, counter: u64, offset: Option<usize>) -> OTPResult {
+        let hash = hash_generic(&counter.to_be_bytes(), &self.secret, &MacDigest::SHA1);
+        self.result_from_hash_at(&hash, offset)
+            .with_counter(counter)
+    }
+
+    /// Generates and returns the HOTP value for a counter given as raw
+    /// bytes, HMACing them as-is.
+    ///
+    /// This is a non-standard escape hatch for proprietary tokens that use a
+    /// moving factor other than the 8-byte big-endian counter from the RFC.
+    /// Passing the 8 big-endian bytes of a counter gives the same code as
+    /// [`HOTP::get_otp`], while any other width gives codes that won't match
+    /// those of a standard authenticator or verifier. As the bytes may not
+    /// be a `u64`, the returned [`OTPResult::counter`] is always 0.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_with_counter_bytes(&self, counter_bytes: &[u8]) -> OTPResult {
+        let hash = hash_generic(counter_bytes, &self.secret, &MacDigest::SHA1);
+        self.result_from_hash(&hash)
+    }
 
+    /// Truncates an HMAC hash into an [`OTPResult`] using the instance's
+    /// digit count.
+    ///
+    /// # Panics
+    /// This method panics if the hash is too short to be truncated.
+    fn result_from_hash(&self, hash: &[u8]) -> OTPResult {
+        dynamic_truncate(hash, self.digits)
+    }
+
+    /// Truncates an HMAC hash into an [`OTPResult`] at the given offset, or
+    /// the dynamic offset if none is given.
+    ///
+    /// # Panics
+    /// This method panics if the hash is too short to be truncated.
+    fn result_from_hash_at(&self, hash: &[u8], offset: Option<usize>) -> OTPResult {
+        let bytes = truncate_hash(hash, offset);
         let code = get_code(bytes, self.digits);
         OTPResult::new(self.digits, code)
     }
 }
+
+/// All otpauth URI methods for the [`HOTP`] struct.
+#[cfg(feature = "std")]
+impl HOTP {
+    /// Generates an otpauth URI for the HOTP instance.
+    ///
+    /// The label is generally the account name, optionally prefixed with the
+    /// issuer and a colon (`Issuer:account`). The issuer parameter is only
+    /// included if one is provided, while the counter is always included as
+    /// it's required for HOTP.
+    ///
+    /// The resulting URI can be read back with
+    /// [`parse_otpauth_uri`](crate::util::parse_otpauth_uri).
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>, counter: u64) -> String {
+        let mut params = vec![("secret", base32_encode(&self.secret))];
+        if let Some(issuer) = issuer {
+            params.push(("issuer", issuer.to_string()));
+        }
+        params.push(("digits", self.digits.to_string()));
+        params.push(("counter", counter.to_string()));
+        for (key, value) in &self.extras {
+            params.push((key, value.clone()));
+        }
+
+        build_otpauth_uri("hotp", label, &params)
+    }
+}
+
+/// All binary serialization methods for the [`HOTP`] struct.
+impl HOTP {
+    /// Serializes the HOTP instance into a compact binary layout.
+    ///
+    /// The layout is a version byte (currently 1), followed by the digit
+    /// count as a single byte, then the secret's length as a big-endian
+    /// `u32` and the secret. The issuer, account name and extra URI
+    /// parameters aren't included, and neither is a counter, which should
+    /// be stored separately. Use [`HOTP::from_bytes`] to read the instance
+    /// back.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::from([BYTES_VERSION, digits_byte(self.digits)]);
+        write_secret(&mut bytes, &self.secret);
+        bytes
+    }
+
+    /// Reads an HOTP instance written by [`HOTP::to_bytes`].
+    ///
+    /// Returns a [`ParseError::InvalidBytes`] if the data is truncated, has
+    /// trailing bytes, or uses an unknown version. The secret and digit
+    /// count are then validated just like [`HOTP::try_new`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = ByteReader::new(bytes)?;
+        let digits = reader.read_u8()? as u32;
+        let secret = reader.read_secret()?;
+        reader.finish()?;
+
+        HOTP::try_new(secret, digits)
+    }
+}
+
+/// All otp verification methods for the [`HOTP`] struct.
+impl HOTP {
+    /// Verifies a code against the HOTP values within a look-ahead window.
+    ///
+    /// Every counter from `counter` up to and including `counter + window`
+    /// is checked. If one of them produces the code, the counter following
+    /// the matched one is returned so the caller can persist it as the next
+    /// expected counter. `None` is returned if no counter in the window
+    /// matched, or if the code matched the counter `u64::MAX`, as the counter
+    /// following it can't be represented.
+    ///
+    /// This follows the resynchronization guidance in [RFC4226 section 7.4].
+    /// A larger window makes it easier for a brute-force attempt to succeed,
+    /// so keep it as small as possible.
+    ///
+    /// Both codes are compared in their zero-padded string form in constant
+    /// time to avoid leaking timing information.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    ///
+    /// [RFC4226 section 7.4]: https://datatracker.ietf.org/doc/html/rfc4226#section-7.4
+    pub fn verify(&self, code: u32, counter: u64, window: u64) -> Option<u64> {
+        self.verify_with_window(code, counter, window)
+            .map(|(next, _)| next)
+    }
+
+    /// Verifies a code against the HOTP values within a look-ahead window,
+    /// also returning how far ahead the matched counter was.
+    ///
+    /// Just like [`HOTP::verify`], the counter following the matched one is
+    /// returned as the next expected counter. It's paired with the number of
+    /// steps between `counter` and the matched counter, which is 0 if the
+    /// code matched `counter` itself. This lets a server enforce its own
+    /// drift policy, such as requiring a second code before accepting a
+    /// large jump ahead.
+    ///
+    /// A code matching the counter `u64::MAX` is rejected, as the counter
+    /// following it can't be represented.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_with_window(&self, code: u32, counter: u64, window: u64) -> Option<(u64, u64)> {
+        let expected = OTPResult::new(self.digits, code).as_string();
+        let count = self.find_counter(&expected, counter, window)?;
+        Some((count.checked_add(1)?, count - counter))
+    }
+
+    /// Verifies a string-formatted code against the HOTP values within a
+    /// look-ahead window.
+    ///
+    /// The code is compared against the zero-padded string given by
+    /// [`OTPResult::as_string`], so leading zeroes are significant.
+    /// Surrounding whitespace is trimmed, and a code that isn't exactly
+    /// [`HOTP::get_digits`] long is rejected without being checked.
+    ///
+    /// Just like [`HOTP::verify`], the counter following the matched one is
+    /// returned, a match at the counter `u64::MAX` is rejected, and codes
+    /// are compared in constant time.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_str(&self, code: &str, counter: u64, window: u64) -> Option<u64> {
+        let code = code.trim();
+        if code.len() != self.digits as usize {
+            return None;
+        }
+        self.find_counter(code, counter, window)?.checked_add(1)
+    }
+
+    /// Finds the counter within the window whose string-formatted code
+    /// matches the given one.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    fn find_counter(&self, expected: &str, counter: u64, window: u64) -> Option<u64> {
+        let last = counter.saturating_add(window);
+        (counter..=last).find(|&count| self.get_otp(count).matches(expected))
+    }
+}
+
+/// A Display implementation for the [`HOTP`] struct
+///
+/// Returns a concise summary of the parameters, such as `HOTP(6 digits)`,
+/// which never includes the secret.
+impl fmt::Display for HOTP {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "HOTP({} digits)", self.digits)
+    }
+}
+
+/// A Debug implementation for the [`HOTP`] struct
+///
+/// The secret is redacted so that logging an instance doesn't leak the key,
+/// with only its length shown (e.g. `secret: [REDACTED; 20 bytes]`).
+impl fmt::Debug for HOTP {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HOTP")
+            .field("secret", &RedactedSecret(self.secret.len()))
+            .field("digits", &self.digits)
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("extras", &self.extras)
+            .finish()
+    }
+}
+
+/// Zeroes out the secret when the [`HOTP`] struct is dropped.
+#[cfg(feature = "zeroize")]
+impl Drop for HOTP {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for HOTP {}
+
+/// Generates arbitrary HOTP instances for fuzzing, with the `arbitrary`
+/// feature.
+///
+/// Just like with [`HOTP::try_new`], the secret is never empty and the
+/// digit count is between 1 and 10.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for HOTP {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let secret = arbitrary_secret(u)?;
+        let digits = u.int_in_range(DIGITS_RANGE)?;
+        let mut hotp = HOTP::from_secret_vec(secret, digits);
+        hotp.issuer = u.arbitrary()?;
+        hotp.account = u.arbitrary()?;
+        Ok(hotp)
+    }
+}
+
+/// A stateful HOTP generator that keeps track of its own counter.
+///
+/// Each call to [`HotpCounter::next_otp`] generates the code for the current
+/// counter and then increments it, just like a hardware token does. The
+/// current counter can be read with [`HotpCounter::counter`] to persist it,
+/// and passed back to [`HotpCounter::new`] to resume where it left off.
+///
+/// # Example
+/// ```rust
+/// use xotp::hotp::{HotpCounter, HOTP};
+///
+/// let mut generator = HotpCounter::new(HOTP::new(b"12345678901234567890", 6), 0);
+/// assert_eq!(generator.next_otp().as_u32(), 755224);
+/// assert_eq!(generator.next_otp().as_u32(), 287082);
+/// assert_eq!(generator.counter(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HotpCounter {
+    hotp: HOTP,
+    counter: u64,
+}
+
+impl HotpCounter {
+    /// Creates a generator for the given HOTP instance, starting at the
+    /// given counter.
+    pub fn new(hotp: HOTP, counter: u64) -> Self {
+        HotpCounter { hotp, counter }
+    }
+
+    /// Gets the counter the next code will be generated with.
+    pub fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    /// Gets the HOTP instance used to generate codes.
+    pub fn hotp(&self) -> &HOTP {
+        &self.hotp
+    }
+
+    /// Generates the code for the current counter, then increments it.
+    ///
+    /// The counter wraps around to 0 after [`u64::MAX`].
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn next_otp(&mut self) -> OTPResult {
+        let result = self.hotp.get_otp(self.counter);
+        self.counter = self.counter.wrapping_add(1);
+        result
+    }
+}
+
+/// The parameters of an [`HOTP`] other than its secret, passed to
+/// [`HOTP::from_config`].
+///
+/// The default is the same as the `default_from_*` constructors, a digit
+/// count of [`DEFAULT_DIGITS`]. With the `serde` feature, missing fields are
+/// filled in with the defaults when deserializing.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct HotpConfig {
+    /// The number of digits of the generated code.
+    pub digits: u32,
+}
+
+impl Default for HotpConfig {
+    fn default() -> Self {
+        HotpConfig {
+            digits: DEFAULT_DIGITS,
+        }
+    }
+}
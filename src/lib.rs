@@ -63,6 +63,13 @@
 //! }
 //! ```
 //!
+//! ## `no_std`
+//!
+//! xotp can be used without the standard library (but with [`alloc`]) by
+//! disabling the default `std` feature. Code generation and verification
+//! work the same way, but the base32 constructors and getters, as well as
+//! building and parsing otpauth URIs, need the `std` feature.
+//!
 //! ## Changelog
 //!
 //! The changelog for this crate can be found at [CHANGELOG.md](https://github.com/tmthecoder/xotp/blob/main/CHANGELOG.md)
@@ -77,7 +84,18 @@
 //! [RFC6238]: https://datatracker.ietf.org/doc/html/rfc6238
 //! [MIT License]: https://github.com/tmthecoder/xotp/blob/main/LICENSE
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod hotp;
 pub mod totp;
 pub mod util;
-pub mod otp_result;
\ No newline at end of file
+pub mod otp_result;
+pub mod throttle;
+
+#[cfg(feature = "migration")]
+mod migration;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
\ No newline at end of file
@@ -1,5 +1,11 @@
-use std::fmt;
-use std::fmt::Formatter;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+use core::hash::{Hash, Hasher};
+
+use crate::util::{constant_time_eq, encode_steam, OtpEncoding};
 
 /// A convenience struct to hold the result of a [`HOTP`] or [`TOTP`]
 /// generation.
@@ -8,21 +14,71 @@ use std::fmt::Formatter;
 /// which will be equal to or less than the digit count. Currently houses
 /// a convenience [`OTPResult::as_string`] which returns a zero-padded string
 /// that has a length of [`OTPResult::digits`]. Additionally, the numerical
-/// representation of the code can be got with [`OTPResult::as_u32`].
+/// representation of the code can be got with [`OTPResult::as_u64`], or
+/// [`OTPResult::as_u32`].
 ///
 /// Returned as a result of either [`HOTP::get_otp`], [`TOTP::get_otp`]
 /// or [`TOTP::get_otp_with_custom_time_start`].
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+///
+/// Results generated by a [`TOTP`] also carry the number of seconds the code
+/// remains valid for, available through [`OTPResult::seconds_remaining`].
+///
+/// Results also carry the counter the code was generated from, available
+/// through [`OTPResult::counter`]. For a [`TOTP`], this is the time step.
+///
+/// If the result uses [`OtpEncoding::Steam`], the code holds the untruncated
+/// value and [`OTPResult::as_string`] renders it as a Steam Guard code.
+///
+/// Two results are equal if they hold the same code, digit count and
+/// encoding. The remaining seconds and the counter don't take part in
+/// equality or hashing, so results generated at different times within the
+/// same period are equal, as is one created with [`OTPResult::new`] for the
+/// same code.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OTPResult {
     digits: u32,
-    code: u32,
+    code: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    seconds_remaining: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    encoding: OtpEncoding,
+    #[cfg_attr(feature = "serde", serde(default))]
+    counter: u64,
 }
 
 /// Constructors for the [`OTPResult`] struct.
 impl OTPResult {
     /// Creates a new instance with the provided digit count and OTP code.
     pub fn new(digits: u32, code: u32 ) -> Self {
-        OTPResult { digits, code }
+        OTPResult::new_u64(digits, code as u64)
+    }
+
+    /// Creates a new instance with the provided digit count and OTP code,
+    /// taking the code as a `u64`.
+    ///
+    /// This allows holding any 10-digit code, where [`OTPResult::new`] is
+    /// limited to those that fit in a `u32`.
+    pub fn new_u64(digits: u32, code: u64) -> Self {
+        OTPResult { digits, code, seconds_remaining: None, encoding: OtpEncoding::Decimal, counter: 0 }
+    }
+
+    /// Sets the encoding used to render the code.
+    pub(crate) fn with_encoding(mut self, encoding: OtpEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the number of seconds the code remains valid for.
+    pub(crate) fn with_seconds_remaining(mut self, seconds_remaining: u64) -> Self {
+        self.seconds_remaining = Some(seconds_remaining);
+        self
+    }
+
+    /// Sets the counter the code was generated from.
+    pub(crate) fn with_counter(mut self, counter: u64) -> Self {
+        self.counter = counter;
+        self
     }
 }
 
@@ -32,6 +88,24 @@ impl OTPResult {
     ///
     /// Also the count used to determine how long the formatted string will be.
     pub fn get_digits(&self) -> u32 { self.digits }
+
+    /// Gets the number of seconds the code remains valid for.
+    ///
+    /// This is only set for codes generated by a [`TOTP`], as [`HOTP`] codes
+    /// have no time component. It's equal to the value returned by
+    /// [`TOTP::time_remaining`] at the time of generation.
+    pub fn seconds_remaining(&self) -> Option<u64> { self.seconds_remaining }
+
+    /// Gets the encoding used to render the code.
+    pub fn get_encoding(&self) -> OtpEncoding { self.encoding }
+
+    /// Gets the counter the code was generated from.
+    ///
+    /// For [`HOTP`] codes this is the counter passed in, while for [`TOTP`]
+    /// codes it's the time step, as returned by [`TOTP::counter_for_time`].
+    /// Results created with [`OTPResult::new`] have a counter of 0. The
+    /// counter isn't compared when checking two results for equality.
+    pub fn counter(&self) -> u64 { self.counter }
 }
 
 /// Convenience code getters for the [`OTPResult`] struct
@@ -40,17 +114,117 @@ impl OTPResult {
     ///
     /// If [`OTPResult::code`] is less than [`OTPResult::digits`] long, leading zeroes
     /// will be added to the string.
+    ///
+    /// With [`OtpEncoding::Steam`], the code is instead rendered as a Steam
+    /// Guard code of length [`OTPResult::digits`].
     pub fn as_string(&self) -> String {
-        format!("{:01$}", self.code as usize, self.digits as usize)
+        match self.encoding {
+            OtpEncoding::Decimal => format!("{:01$}", self.code, self.digits as usize),
+            OtpEncoding::Steam => encode_steam(self.code as u32, self.digits),
+        }
     }
 
+    /// Returns the OTP as a formatted string, zero-padded to be at least
+    /// `width` long.
+    ///
+    /// The string is never shorter than [`OTPResult::as_string`], so the
+    /// length is the larger of `width` and [`OTPResult::digits`]. This helps
+    /// align codes with differing digit counts, such as in a table. Codes
+    /// using [`OtpEncoding::Steam`] aren't numbers, so they're returned
+    /// unpadded.
+    pub fn as_string_padded(&self, width: usize) -> String {
+        match self.encoding {
+            OtpEncoding::Decimal => {
+                let width = width.max(self.digits as usize);
+                format!("{:01$}", self.code, width)
+            }
+            OtpEncoding::Steam => self.as_string(),
+        }
+    }
+
+    /// Returns the OTP as a formatted string, with a separator inserted
+    /// every `group` characters.
+    ///
+    /// The code is zero-padded just like [`OTPResult::as_string`], then split
+    /// into groups from the left, so a length that isn't a multiple of
+    /// `group` leaves a shorter last group (`"123 456 7"`). A `group` of 0
+    /// is treated as a single group, returning the code unchanged.
+    pub fn as_grouped_string(&self, group: usize, sep: &str) -> String {
+        let code = self.as_string();
+        if group == 0 {
+            return code;
+        }
+        code.as_bytes()
+            .chunks(group)
+            .map(|chunk| core::str::from_utf8(chunk).expect("Codes are ASCII"))
+            .collect::<Vec<_>>()
+            .join(sep)
+    }
+
+    /// Returns the OTP as a formatted string split into space-separated
+    /// groups, the way authenticator apps display codes.
+    ///
+    /// Codes are split into groups of 3 (`"123 456"`), unless their length
+    /// is a multiple of 4 but not of 3, in which case groups of 4 are used
+    /// (`"1234 5678"`).
+    pub fn as_grouped(&self) -> String {
+        let length = self.as_string().len();
+        let group = if length % 3 != 0 && length % 4 == 0 {
+            4
+        } else {
+            3
+        };
+        self.as_grouped_string(group, " ")
+    }
+
+    /// Returns each decimal digit of the OTP, most-significant first.
+    ///
+    /// Just like [`OTPResult::as_string`], the digits are zero-padded to be
+    /// [`OTPResult::digits`] long. This is only meaningful for codes using
+    /// [`OtpEncoding::Decimal`].
+    pub fn as_digits(&self) -> Vec<u8> {
+        let mut code = self.code;
+        let mut digits: Vec<u8> = (0..self.digits)
+            .map(|_| {
+                let digit = (code % 10) as u8;
+                code /= 10;
+                digit
+            })
+            .collect();
+        digits.reverse();
+        digits
+    }
 
     /// Returns the OTP as it's original numerical representation
     ///
-    /// This number may not be [`OTPResult::digits`] long.
-    pub fn as_u32(&self) -> u32 {
+    /// This number may not be [`OTPResult::digits`] long. With
+    /// [`OtpEncoding::Steam`], this is the untruncated value the Steam Guard
+    /// code is derived from.
+    pub fn as_u64(&self) -> u64 {
         self.code
     }
+
+    /// Returns the OTP as it's original numerical representation, truncated
+    /// to a `u32`.
+    ///
+    /// Generated codes are at most 31 bits, so this is the same as
+    /// [`OTPResult::as_u64`] for any code returned by [`HOTP`] or [`TOTP`].
+    /// Only codes created with [`OTPResult::new_u64`] can be truncated.
+    pub fn as_u32(&self) -> u32 {
+        self.code as u32
+    }
+
+    /// Checks whether the given input is the code, as rendered by
+    /// [`OTPResult::as_string`].
+    ///
+    /// Surrounding whitespace is trimmed from the input, and leading zeroes
+    /// are significant, so `"1234"` doesn't match a 6-digit code of 1234.
+    /// The comparison is done in constant time, so the time taken doesn't
+    /// depend on how much of the input matched. Only an input of the wrong
+    /// length returns early, as the length of a code isn't secret.
+    pub fn matches(&self, input: &str) -> bool {
+        constant_time_eq(self.as_string().as_bytes(), input.trim().as_bytes())
+    }
 }
 
 /// A Display implementation for the [`OTPResult`] struct
@@ -61,4 +235,26 @@ impl fmt::Display for OTPResult {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
        write!(f, "{}", self.as_string())
     }
+}
+
+/// A PartialEq implementation for the [`OTPResult`] struct
+///
+/// Compares the code, digit count and encoding, ignoring the metadata set
+/// on generated results.
+impl PartialEq for OTPResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits == other.digits && self.code == other.code && self.encoding == other.encoding
+    }
+}
+
+impl Eq for OTPResult {}
+
+/// A Hash implementation for the [`OTPResult`] struct, consistent with its
+/// [`PartialEq`] implementation.
+impl Hash for OTPResult {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.digits.hash(state);
+        self.code.hash(state);
+        self.encoding.hash(state);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,198 @@
+// Rate-limited verification to slow down brute-force attempts
+
+//! Verification that refuses to check codes after too many failed attempts.
+//!
+//! A [`ThrottledVerifier`] wraps a [`TOTP`] or [`HOTP`] instance and counts
+//! the failed attempts made against each moving factor, which is the time
+//! step for a [`TOTP`] and the counter for an [`HOTP`]. Once the limit is
+//! reached for a moving factor, codes are no longer checked against it and
+//! [`ThrottleResult::RateLimited`] is returned, even for a correct code.
+//!
+//! The failed attempts are kept in an [`AttemptStore`], so they can be
+//! persisted alongside the user they belong to. [`MemoryAttemptStore`] keeps
+//! them in memory. Each user should have their own store, as attempts
+//! aren't keyed by user.
+//!
+//! # Example
+//! ```rust
+//! use xotp::throttle::{ThrottleResult, ThrottledVerifier};
+//! use xotp::totp::{VerifyResult, TOTP};
+//!
+//! let totp = TOTP::default_from_utf8("12345678901234567890");
+//! let mut verifier = ThrottledVerifier::new(totp, 3);
+//! for _ in 0..3 {
+//!     let result = verifier.verify(0, 59, 1);
+//!     assert_eq!(result, ThrottleResult::Checked(VerifyResult::Invalid));
+//! }
+//! assert_eq!(verifier.verify(287082, 59, 1), ThrottleResult::RateLimited);
+//! ```
+
+use crate::hotp::HOTP;
+use crate::totp::{VerifyResult, TOTP};
+use alloc::collections::BTreeMap;
+
+/// A store of the failed verification attempts made against each moving
+/// factor, used by a [`ThrottledVerifier`].
+pub trait AttemptStore {
+    /// Gets the number of failed attempts recorded for the moving factor.
+    fn failures(&self, factor: u64) -> u32;
+
+    /// Records a failed attempt for the moving factor.
+    fn record_failure(&mut self, factor: u64);
+}
+
+/// An [`AttemptStore`] keeping the failed attempts in memory.
+///
+/// Moving factors only increase over time, so the attempts made against
+/// earlier moving factors are dropped whenever a failure is recorded for a
+/// later one, keeping the store small.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAttemptStore {
+    failures: BTreeMap<u64, u32>,
+}
+
+impl MemoryAttemptStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        MemoryAttemptStore::default()
+    }
+}
+
+impl AttemptStore for MemoryAttemptStore {
+    fn failures(&self, factor: u64) -> u32 {
+        self.failures.get(&factor).copied().unwrap_or(0)
+    }
+
+    fn record_failure(&mut self, factor: u64) {
+        self.failures.retain(|&key, _| key >= factor);
+        let failures = self.failures.entry(factor).or_insert(0);
+        *failures = failures.saturating_add(1);
+    }
+}
+
+/// The outcome of verifying a code with a [`ThrottledVerifier`].
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ThrottleResult {
+    /// The code was checked, with the given outcome.
+    Checked(VerifyResult),
+    /// The code wasn't checked, as too many attempts had failed for the
+    /// moving factor.
+    RateLimited,
+}
+
+impl ThrottleResult {
+    /// Returns whether the code was checked and matched, either exactly or
+    /// within the skew or window.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ThrottleResult::Checked(result) if result.is_valid())
+    }
+}
+
+/// A verifier refusing to check codes once too many attempts have failed
+/// for the current moving factor.
+///
+/// Wraps a [`TOTP`] or [`HOTP`] instance along with an [`AttemptStore`],
+/// which defaults to a [`MemoryAttemptStore`]. After `max_failures` failed
+/// attempts for a moving factor, [`ThrottledVerifier::verify`] returns
+/// [`ThrottleResult::RateLimited`] without checking the code.
+#[derive(Debug, Clone)]
+pub struct ThrottledVerifier<G, S = MemoryAttemptStore> {
+    generator: G,
+    store: S,
+    max_failures: u32,
+}
+
+impl<G> ThrottledVerifier<G> {
+    /// Creates a verifier allowing `max_failures` failed attempts for each
+    /// moving factor, keeping them in a [`MemoryAttemptStore`].
+    pub fn new(generator: G, max_failures: u32) -> Self {
+        ThrottledVerifier::with_store(generator, MemoryAttemptStore::new(), max_failures)
+    }
+}
+
+impl<G, S: AttemptStore> ThrottledVerifier<G, S> {
+    /// Creates a verifier allowing `max_failures` failed attempts for each
+    /// moving factor, keeping them in the given store.
+    pub fn with_store(generator: G, store: S, max_failures: u32) -> Self {
+        ThrottledVerifier {
+            generator,
+            store,
+            max_failures,
+        }
+    }
+
+    /// Gets the wrapped [`TOTP`] or [`HOTP`] instance.
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// Gets the store of failed attempts.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Gets the number of failed attempts allowed for each moving factor.
+    pub fn max_failures(&self) -> u32 {
+        self.max_failures
+    }
+
+    /// Checks whether the moving factor has no attempts left.
+    pub fn is_rate_limited(&self, factor: u64) -> bool {
+        self.store.failures(factor) >= self.max_failures
+    }
+
+    /// Runs a verification unless the moving factor is rate limited,
+    /// recording a failure if it's invalid.
+    fn throttle(&mut self, factor: u64, verify: impl FnOnce(&G) -> VerifyResult) -> ThrottleResult {
+        if self.is_rate_limited(factor) {
+            return ThrottleResult::RateLimited;
+        }
+        let result = verify(&self.generator);
+        if !result.is_valid() {
+            self.store.record_failure(factor);
+        }
+        ThrottleResult::Checked(result)
+    }
+}
+
+/// Verification for a wrapped [`TOTP`] instance.
+impl<S: AttemptStore> ThrottledVerifier<TOTP, S> {
+    /// Verifies a code just like [`TOTP::verify_detailed`], unless too many
+    /// attempts have failed for the time step of the specified time.
+    ///
+    /// Failed attempts are counted against the time step of the specified
+    /// time, as returned by [`TOTP::counter_for_time`], so the limit resets
+    /// once a new code is generated.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify(&mut self, code: u32, time: u64, skew: u64) -> ThrottleResult {
+        let step = self.generator.counter_for_time(time);
+        self.throttle(step, |totp| totp.verify_detailed(code, time, skew))
+    }
+}
+
+/// Verification for a wrapped [`HOTP`] instance.
+impl<S: AttemptStore> ThrottledVerifier<HOTP, S> {
+    /// Verifies a code against the HOTP values within a look-ahead window
+    /// just like [`HOTP::verify_with_window`], unless too many attempts have
+    /// failed for the counter.
+    ///
+    /// A checked [`VerifyResult::Valid`] is returned if the code matched
+    /// `counter` itself, and [`VerifyResult::ValidWithinSkew`] with the
+    /// number of steps ahead if it matched a later counter. Either way, the next
+    /// expected counter is the matched one plus 1. Failed attempts are
+    /// counted against `counter`, so the limit resets once it's advanced.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify(&mut self, code: u32, counter: u64, window: u64) -> ThrottleResult {
+        self.throttle(counter, |hotp| {
+            match hotp.verify_with_window(code, counter, window) {
+                Some((_, 0)) => VerifyResult::Valid,
+                Some((_, ahead)) => VerifyResult::ValidWithinSkew(ahead as i64),
+                None => VerifyResult::Invalid,
+            }
+        })
+    }
+}
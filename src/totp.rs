@@ -1,5 +1,41 @@
 use crate::otp_result::OTPResult;
-use crate::util::{base32_decode, get_code, hash_generic, MacDigest};
+#[cfg(feature = "arbitrary")]
+use crate::util::arbitrary_secret;
+#[cfg(feature = "std")]
+use crate::util::{
+    base32_decode, base32_decode_with_alphabet, base32_encode, build_otpauth_uri,
+    escape_label_part, Base32Alphabet, SystemClock,
+};
+#[cfg(feature = "base64")]
+use crate::util::{base64_decode, base64_encode};
+use crate::util::{
+    check_secret_len, check_secret_not_empty, digits_byte, dynamic_truncate, get_code,
+    get_truncated_value, hash_generic, hash_internal, hex_decode, hex_encode, truncate_hash,
+    write_secret, ByteReader, Clock, KeyedHmac, MacDigest, OtpEncoding, OtpError, ParseError,
+    RedactedSecret, ValueError, BYTES_VERSION, DIGITS_RANGE, STEAM_CODE_LENGTH,
+};
+#[cfg(feature = "qr")]
+use crate::util::{qr_png, qr_svg};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+use core::time::Duration;
+use hmac::digest::KeyInit;
+use hmac::Mac;
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The digit count used when none is given, the same as for HOTP.
+pub const DEFAULT_DIGITS: u32 = crate::hotp::DEFAULT_DIGITS;
+
+/// The period in seconds used when none is given, as recommended by
+/// [RFC6238].
+///
+/// [RFC6238]: https://datatracker.ietf.org/doc/html/rfc6238
+pub const DEFAULT_PERIOD: u64 = 30;
 
 /// A TOTP generator
 ///
@@ -15,13 +51,20 @@ use crate::util::{base32_decode, get_code, hash_generic, MacDigest};
 /// In addition to the example, all other initialization methods can be
 /// utilized in a similar manner.
 ///
+/// Equality and hashing both cover every field, including the secret, so
+/// instances can be used as `HashMap` or `HashSet` keys.
+///
 /// [RFC6238]: https://datatracker.ietf.org/doc/html/rfc6238
-#[derive(Debug, Clone, Hash)]
+#[derive(Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TOTP {
     /// The secret key used in the HMAC process.
     ///
     /// Often given as a Base32 key, which can be conveniently initialized
     /// using the [`TOTP::default_from_base32`] constructor.
+    ///
+    /// When serialized, the secret is base32-encoded.
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::base32_serde"))]
     secret: Vec<u8>,
 
     /// The digest to use in the HMAC process.
@@ -32,13 +75,51 @@ pub struct TOTP {
 
     /// The number of digits of the code generated.
     ///
-    /// This value defaults to 6 if not specified in a constructor.
+    /// This value defaults to [`DEFAULT_DIGITS`] if not specified in a
+    /// constructor.
     digits: u32,
 
     /// The period in seconds between two different generated code.
     ///
-    /// This value defaults to 30 if not specified in a constructor.
+    /// This value defaults to [`DEFAULT_PERIOD`] if not specified in a
+    /// constructor.
     period: u64,
+
+    /// The start time (T0) in seconds that time steps are counted from.
+    ///
+    /// This value defaults to 0 (the Unix epoch), and can be changed with
+    /// [`TOTP::with_time_start`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    time_start: u64,
+
+    /// The issuer of the account the TOTP belongs to, if known.
+    ///
+    /// This value is set when parsed from an otpauth URI, or with
+    /// [`TOTP::with_issuer`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    issuer: Option<String>,
+
+    /// The name of the account the TOTP belongs to, if known.
+    ///
+    /// This value is set when parsed from an otpauth URI, or with
+    /// [`TOTP::with_account`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    account: Option<String>,
+
+    /// The encoding used to render generated codes.
+    ///
+    /// This value defaults to [`OtpEncoding::Decimal`], and can be changed
+    /// with [`TOTP::with_encoding`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    encoding: OtpEncoding,
+
+    /// Query parameters of the otpauth URI the TOTP was parsed from that
+    /// aren't otherwise recognized, such as a provider's `image`.
+    ///
+    /// These are included again by [`TOTP::to_uri`], so vendor extensions
+    /// survive an import and export.
+    #[cfg_attr(feature = "serde", serde(default))]
+    extras: BTreeMap<String, String>,
 }
 
 /// All initializer implementations for the [`TOTP`] struct
@@ -46,13 +127,136 @@ impl TOTP {
     /// Generates a new TOTP instance from a byte array representation of the
     /// secret, a digest algorithm, a number of digits,
     /// and a period in seconds.
+    ///
+    /// No validation is done on the secret, digit count or period. An empty
+    /// secret generates codes that look valid but are meaningless, a digit
+    /// count of 0 generates a code of 0 every time, and a period of 0 makes
+    /// code generation panic. Use [`TOTP::try_new`] to reject these values.
     pub fn new(secret: &[u8], mac_digest: MacDigest, digits: u32, period: u64) -> Self {
+        TOTP::from_secret_vec(secret.to_vec(), mac_digest, digits, period)
+    }
+
+    /// Generates a new TOTP instance from an owned byte vector of the
+    /// secret, a digest algorithm, a number of digits,
+    /// and a period in seconds.
+    ///
+    /// Unlike [`TOTP::new`], the vector is moved into the instance rather
+    /// than copied, so no other copy of the secret is left in memory. Just
+    /// like [`TOTP::new`], no validation is done on the secret, digit count
+    /// or period, so an empty vector generates codes that look valid but are
+    /// meaningless. Use [`TOTP::try_new`] to reject an empty secret.
+    pub fn from_secret_vec(
+        secret: Vec<u8>,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Self {
         TOTP {
-            secret: secret.to_vec(),
+            secret,
             mac_digest,
             digits,
             period,
+            time_start: 0,
+            issuer: None,
+            account: None,
+            encoding: OtpEncoding::Decimal,
+            extras: BTreeMap::new(),
+        }
+    }
+
+    /// Generates a new TOTP instance from a byte array representation of the
+    /// secret and the digest, digit count, period and start time of a
+    /// [`TotpConfig`].
+    ///
+    /// Just like [`TOTP::new`], no validation is done on the secret, digit
+    /// count or period.
+    pub fn from_config(secret: &[u8], config: &TotpConfig) -> Self {
+        TOTP::new(secret, config.mac_digest, config.digits, config.period)
+            .with_time_start(config.time_start)
+    }
+
+    /// Generates a new TOTP instance from a byte array representation of the
+    /// secret, a digest algorithm, a number of digits,
+    /// and a period in seconds, validating the secret, digit count and period.
+    ///
+    /// Returns a [`ParseError::EmptySecret`] if the secret is empty, a
+    /// [`ParseError::WrongDigitNumber`] if the digit count isn't between 1
+    /// and 10, or a [`ParseError::InvalidPeriod`] if the period is 0.
+    pub fn try_new(
+        secret: &[u8],
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        TOTP::check_params(secret, digits, period)?;
+        Ok(TOTP::new(secret, mac_digest, digits, period))
+    }
+
+    /// Generates a new TOTP instance just like [`TOTP::try_new`], but also
+    /// rejects secrets shorter than [`MIN_SECRET_BYTES`].
+    ///
+    /// Returns a [`ParseError::SecretTooShort`] with the secret's length if
+    /// it's too short, on top of the errors returned by [`TOTP::try_new`].
+    /// Use this over the other constructors when handling secrets from
+    /// untrusted sources.
+    ///
+    /// [`MIN_SECRET_BYTES`]: crate::util::MIN_SECRET_BYTES
+    pub fn try_new_checked(
+        secret: &[u8],
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        let totp = TOTP::try_new(secret, mac_digest, digits, period)?;
+        check_secret_len(secret)?;
+        Ok(totp)
+    }
+
+    /// Generates a new TOTP instance just like [`TOTP::try_new`], but with
+    /// the period given as a [`Duration`].
+    ///
+    /// The period is stored in whole seconds, so a [`ParseError::InvalidPeriod`]
+    /// is returned if it's shorter than a second ([`ValueError::OutOfRange`])
+    /// or has a fractional part ([`ValueError::Fractional`]), on top of the
+    /// errors returned by [`TOTP::try_new`].
+    pub fn with_period_duration(
+        secret: &[u8],
+        mac_digest: MacDigest,
+        digits: u32,
+        period: Duration,
+    ) -> Result<Self, ParseError> {
+        if period.as_secs() == 0 {
+            return Err(ParseError::InvalidPeriod {
+                value: format!("{:?}", period),
+                reason: ValueError::OutOfRange,
+            });
         }
+        if period.subsec_nanos() != 0 {
+            return Err(ParseError::InvalidPeriod {
+                value: format!("{:?}", period),
+                reason: ValueError::Fractional,
+            });
+        }
+        TOTP::try_new(secret, mac_digest, digits, period.as_secs())
+    }
+
+    /// Checks the secret isn't empty, the digit count is between 1 and 10 and
+    /// the period isn't 0.
+    fn check_params(secret: &[u8], digits: u32, period: u64) -> Result<(), ParseError> {
+        check_secret_not_empty(secret)?;
+        if !DIGITS_RANGE.contains(&digits) {
+            return Err(ParseError::WrongDigitNumber {
+                value: digits.to_string(),
+                reason: ValueError::OutOfRange,
+            });
+        }
+        if period == 0 {
+            return Err(ParseError::InvalidPeriod {
+                value: period.to_string(),
+                reason: ValueError::OutOfRange,
+            });
+        }
+        Ok(())
     }
 
     /// Generates a new TOTP instance from an utf8 representation of the
@@ -68,9 +272,12 @@ impl TOTP {
     ///
     /// # Panics
     /// This method panics if the provided string is not correctly base32 encoded.
+    /// Use [`TOTP::try_from_base32`] to handle invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
     pub fn new_from_base32(secret: &str, mac_digest: MacDigest, digits: u32, period: u64) -> Self {
-        let decoded = base32_decode(secret).expect("Failed to decode base32 string");
-        TOTP::new(&decoded, mac_digest, digits, period)
+        TOTP::try_from_base32(secret, mac_digest, digits, period)
+            .expect("Failed to decode base32 string")
     }
 
     /// Creates a new TOTP instance with a byte-array representation of the
@@ -87,7 +294,11 @@ impl TOTP {
     ///
     /// Defaults to a 6-digit OTP output and a 30-second period.
     pub fn default_from_secret_with_digest(secret: &[u8], mac_digest: MacDigest) -> Self {
-        TOTP::new(secret, mac_digest, 6, 30)
+        let config = TotpConfig {
+            mac_digest,
+            ..TotpConfig::default()
+        };
+        TOTP::from_config(secret, &config)
     }
 
     /// Creates a new TOTP instance with an utf8 representation of the secret.
@@ -103,7 +314,7 @@ impl TOTP {
     ///
     /// Defaults to a 6-digit OTP output and a 30-second period.
     pub fn default_from_utf8_with_digest(secret: &str, mac_digest: MacDigest) -> Self {
-        TOTP::new_from_utf8(secret, mac_digest, 6, 30)
+        TOTP::default_from_secret_with_digest(secret.as_bytes(), mac_digest)
     }
 
     /// Creates a new TOTP instance with a base32 representation of the secret.
@@ -113,7 +324,10 @@ impl TOTP {
     ///
     /// # Panics
     /// This method panics if the provided string is not correctly
-    /// base32-encoded.
+    /// base32-encoded. Use [`TOTP::try_default_from_base32`] to handle
+    /// invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
     pub fn default_from_base32(secret: &str) -> Self {
         TOTP::default_from_base32_with_digest(secret, MacDigest::SHA1)
     }
@@ -125,9 +339,273 @@ impl TOTP {
     ///
     /// # Panics
     /// This method panics if the provided string is not correctly
-    /// base32-encoded.
+    /// base32-encoded. Use [`TOTP::try_default_from_base32_with_digest`] to
+    /// handle invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
     pub fn default_from_base32_with_digest(secret: &str, mac_digest: MacDigest) -> Self {
-        TOTP::new_from_base32(secret, mac_digest, 6, 30)
+        TOTP::new_from_base32(secret, mac_digest, DEFAULT_DIGITS, DEFAULT_PERIOD)
+    }
+
+    /// Generates a new TOTP instance from a base32-encoded representation of
+    /// the secret, a digest algorithm, a number of digits,
+    /// and a period in seconds.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly base32-encoded, or a [`ParseError::EmptySecret`] if
+    /// it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_from_base32(
+        secret: &str,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        TOTP::try_from_base32_with_alphabet(
+            secret,
+            Base32Alphabet::Rfc4648,
+            mac_digest,
+            digits,
+            period,
+        )
+    }
+
+    /// Generates a new TOTP instance from a representation of the secret
+    /// encoded with the given base32 alphabet, a digest algorithm, a number
+    /// of digits, and a period in seconds.
+    ///
+    /// # Panics
+    /// This method panics if the provided string is not correctly encoded
+    /// with the alphabet. Use [`TOTP::try_from_base32_with_alphabet`] to
+    /// handle invalid input instead.
+    /// It also panics if the string decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn new_from_base32_with_alphabet(
+        secret: &str,
+        alphabet: Base32Alphabet,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Self {
+        TOTP::try_from_base32_with_alphabet(secret, alphabet, mac_digest, digits, period)
+            .expect("Failed to decode base32 string")
+    }
+
+    /// Generates a new TOTP instance from a representation of the secret
+    /// encoded with the given base32 alphabet, a digest algorithm, a number
+    /// of digits, and a period in seconds.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly encoded with the alphabet, or a
+    /// [`ParseError::EmptySecret`] if it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_from_base32_with_alphabet(
+        secret: &str,
+        alphabet: Base32Alphabet,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        match base32_decode_with_alphabet(secret, alphabet) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(TOTP::from_secret_vec(decoded, mac_digest, digits, period))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new TOTP instance with a base32 representation of the secret.
+    ///
+    /// Defaults to using [`MacDigest::SHA1`] as the digest for HMAC
+    /// operations, with a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly base32-encoded, or a [`ParseError::EmptySecret`] if
+    /// it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_default_from_base32(secret: &str) -> Result<Self, ParseError> {
+        TOTP::try_default_from_base32_with_digest(secret, MacDigest::SHA1)
+    }
+
+    /// Creates a new TOTP instance with a base32 representation of the secret
+    /// and a digest algorithm.
+    ///
+    /// Defaults to a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// is not correctly base32-encoded, or a [`ParseError::EmptySecret`] if
+    /// it decodes to an empty secret.
+    #[cfg(feature = "std")]
+    pub fn try_default_from_base32_with_digest(
+        secret: &str,
+        mac_digest: MacDigest,
+    ) -> Result<Self, ParseError> {
+        TOTP::try_from_base32(secret, mac_digest, DEFAULT_DIGITS, DEFAULT_PERIOD)
+    }
+
+    /// Generates a new TOTP instance from a hex-encoded representation of
+    /// the secret, a digest algorithm, a number of digits,
+    /// and a period in seconds.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// has an odd length or contains non-hex characters, or a
+    /// [`ParseError::EmptySecret`] if it's empty.
+    pub fn new_from_hex(
+        secret: &str,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        match hex_decode(secret) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(TOTP::from_secret_vec(decoded, mac_digest, digits, period))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new TOTP instance with a hex representation of the secret.
+    ///
+    /// Defaults to using [`MacDigest::SHA1`] as the digest for HMAC
+    /// operations, with a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// has an odd length or contains non-hex characters, or a
+    /// [`ParseError::EmptySecret`] if it's empty.
+    pub fn default_from_hex(secret: &str) -> Result<Self, ParseError> {
+        TOTP::default_from_hex_with_digest(secret, MacDigest::SHA1)
+    }
+
+    /// Creates a new TOTP instance with a hex representation of the secret
+    /// and a digest algorithm.
+    ///
+    /// Defaults to a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// has an odd length or contains non-hex characters, or a
+    /// [`ParseError::EmptySecret`] if it's empty.
+    pub fn default_from_hex_with_digest(
+        secret: &str,
+        mac_digest: MacDigest,
+    ) -> Result<Self, ParseError> {
+        TOTP::new_from_hex(secret, mac_digest, DEFAULT_DIGITS, DEFAULT_PERIOD)
+    }
+
+    /// Generates a new TOTP instance from a base64-encoded representation of
+    /// the secret, a digest algorithm, a number of digits,
+    /// and a period in seconds.
+    ///
+    /// Both the standard and URL-safe alphabets are accepted, with or without
+    /// padding. Returns a [`ParseError::SecretParsingError`] if the provided
+    /// string isn't correctly base64-encoded, or a [`ParseError::EmptySecret`]
+    /// if it decodes to an empty secret. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn new_from_base64(
+        secret: &str,
+        mac_digest: MacDigest,
+        digits: u32,
+        period: u64,
+    ) -> Result<Self, ParseError> {
+        match base64_decode(secret) {
+            Some(decoded) => {
+                check_secret_not_empty(&decoded)?;
+                Ok(TOTP::from_secret_vec(decoded, mac_digest, digits, period))
+            }
+            None => Err(ParseError::SecretParsingError(String::from(secret))),
+        }
+    }
+
+    /// Creates a new TOTP instance with a base64 representation of the
+    /// secret.
+    ///
+    /// Defaults to using [`MacDigest::SHA1`] as the digest for HMAC
+    /// operations, with a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// isn't correctly base64-encoded, or a [`ParseError::EmptySecret`] if it
+    /// decodes to an empty secret. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn default_from_base64(secret: &str) -> Result<Self, ParseError> {
+        TOTP::default_from_base64_with_digest(secret, MacDigest::SHA1)
+    }
+
+    /// Creates a new TOTP instance with a base64 representation of the
+    /// secret and a digest algorithm.
+    ///
+    /// Defaults to a 6-digit OTP output and a 30-second period.
+    ///
+    /// Returns a [`ParseError::SecretParsingError`] if the provided string
+    /// isn't correctly base64-encoded, or a [`ParseError::EmptySecret`] if it
+    /// decodes to an empty secret. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn default_from_base64_with_digest(
+        secret: &str,
+        mac_digest: MacDigest,
+    ) -> Result<Self, ParseError> {
+        TOTP::new_from_base64(secret, mac_digest, DEFAULT_DIGITS, DEFAULT_PERIOD)
+    }
+
+    /// Creates a [`TotpBuilder`] to configure a TOTP instance field by field.
+    ///
+    /// Any field that isn't set takes the same default as the `default_from_*`
+    /// constructors: [`MacDigest::SHA1`], 6 digits and a 30-second period.
+    pub fn builder() -> TotpBuilder {
+        TotpBuilder::default()
+    }
+
+    /// Sets the issuer and account name the TOTP belongs to.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_label(mut self, issuer: Option<String>, account: Option<String>) -> Self {
+        self.issuer = issuer;
+        self.account = account;
+        self
+    }
+
+    /// Sets the unrecognized query parameters of the URI the TOTP was
+    /// parsed from.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_extras(mut self, extras: BTreeMap<String, String>) -> Self {
+        self.extras = extras;
+        self
+    }
+
+    /// Sets the issuer of the account the TOTP belongs to.
+    ///
+    /// The issuer defaults to `None` with all other constructors.
+    pub fn with_issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(String::from(issuer));
+        self
+    }
+
+    /// Sets the name of the account the TOTP belongs to.
+    ///
+    /// The account name defaults to `None` with all other constructors.
+    pub fn with_account(mut self, account: &str) -> Self {
+        self.account = Some(String::from(account));
+        self
+    }
+
+    /// Sets the encoding used to render generated codes.
+    ///
+    /// Using [`OtpEncoding::Steam`] generates 5-character Steam Guard codes,
+    /// in which case the digit count is ignored. The encoding defaults to
+    /// [`OtpEncoding::Decimal`] with all other constructors.
+    pub fn with_encoding(mut self, encoding: OtpEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets the start time (T0) in seconds that time steps are counted from.
+    ///
+    /// Methods such as [`TOTP::get_otp`] and [`TOTP::verify`] then use this
+    /// start time, rather than it having to be passed to
+    /// [`TOTP::get_otp_with_custom_time_start`] on every call. The start
+    /// time defaults to 0 (the Unix epoch) with all other constructors.
+    pub fn with_time_start(mut self, time_start: u64) -> Self {
+        self.time_start = time_start;
+        self
     }
 }
 
@@ -138,6 +616,15 @@ impl TOTP {
         self.mac_digest
     }
 
+    /// Gets the name of the algorithm used for code generation, such as
+    /// `"SHA1"`, for display.
+    ///
+    /// This is the same name given by the [`MacDigest`]'s `Display`
+    /// implementation.
+    pub fn get_algorithm_name(&self) -> &'static str {
+        self.mac_digest.name()
+    }
+
     /// Gets the number of digits of the code.
     pub fn get_digits(&self) -> u32 {
         self.digits
@@ -147,6 +634,56 @@ impl TOTP {
     pub fn get_period(&self) -> u64 {
         self.period
     }
+
+    /// Gets the raw bytes of the secret.
+    pub fn get_secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Gets the secret as a base32 string, using the same RFC4648 alphabet
+    /// (without padding) the base32 constructors decode.
+    #[cfg(feature = "std")]
+    pub fn get_secret_base32(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// Gets the secret as a lowercase hex string.
+    pub fn get_secret_hex(&self) -> String {
+        hex_encode(&self.secret)
+    }
+
+    /// Gets the secret as a padded base64 string, using the standard
+    /// alphabet. Requires the `base64` feature.
+    #[cfg(feature = "base64")]
+    pub fn get_secret_base64(&self) -> String {
+        base64_encode(&self.secret)
+    }
+
+    /// Gets the issuer of the account the TOTP belongs to, if known.
+    pub fn get_issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    /// Gets the name of the account the TOTP belongs to, if known.
+    pub fn get_account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    /// Gets the encoding used to render generated codes.
+    pub fn get_encoding(&self) -> OtpEncoding {
+        self.encoding
+    }
+
+    /// Gets the start time (T0) in seconds that time steps are counted from.
+    pub fn get_time_start(&self) -> u64 {
+        self.time_start
+    }
+
+    /// Gets the unrecognized query parameters of the otpauth URI the TOTP
+    /// was parsed from, which is empty for all other instances.
+    pub fn get_extras(&self) -> &BTreeMap<String, String> {
+        &self.extras
+    }
 }
 
 /// All helper methods for totp generation
@@ -156,8 +693,10 @@ impl TOTP {
     ///
     /// Just like the corresponding [`TOTP::get_otp`] method, this method
     /// takes the current system time in seconds.
+    ///
+    /// This is an alias of [`TOTP::time_remaining`].
     pub fn time_until_refresh(&self, time: u64) -> u64 {
-        self.time_until_refresh_with_start(time, 0)
+        self.time_remaining(time)
     }
 
     /// Returns the time in seconds until an OTP refresh is needed.
@@ -166,9 +705,95 @@ impl TOTP {
     /// method, this method takes the current time in seconds along with a
     /// specified start time in case an offset is desired. Both values must be
     /// in seconds.
+    ///
+    /// This is an alias of [`TOTP::time_remaining_with_start`].
     pub fn time_until_refresh_with_start(&self, time: u64, time_start: u64) -> u64 {
-        let time_until = (time - time_start) % self.period;
-        if time_until == 0 { self.period } else { time_until }
+        self.time_remaining_with_start(time, time_start)
+    }
+
+    /// Returns the number of seconds left in the current period.
+    ///
+    /// Takes the current system time in seconds, just like [`TOTP::get_otp`].
+    /// At the exact start of a period the full period is returned rather
+    /// than 0, as the freshly generated code is valid for all of it.
+    ///
+    /// Periods are counted from the instance's start time, which defaults to
+    /// 0.
+    pub fn time_remaining(&self, time: u64) -> u64 {
+        self.time_remaining_with_start(time, self.time_start)
+    }
+
+    /// Returns the number of seconds left in the current period, using a
+    /// custom start time.
+    ///
+    /// Takes the current time and start time in seconds, just like
+    /// [`TOTP::get_otp_with_custom_time_start`]. At the exact start of a
+    /// period the full period is returned rather than 0.
+    ///
+    /// If the time is before the start time, it's treated as being the start
+    /// time and the full period is returned.
+    pub fn time_remaining_with_start(&self, time: u64, time_start: u64) -> u64 {
+        self.period - (time.saturating_sub(time_start) % self.period)
+    }
+
+    /// Returns the instant the code generated at the given instant expires,
+    /// which is the start of the next period.
+    ///
+    /// This allows a refresh to be scheduled for the exact rollover, rather
+    /// than polling [`TOTP::time_remaining`]. Instants before the Unix epoch
+    /// are treated as the epoch itself. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn expires_at(&self, now: std::time::SystemTime) -> std::time::SystemTime {
+        self.expires_at_with_start(now, self.time_start)
+    }
+
+    /// Returns the instant the code generated at the given instant expires,
+    /// using a custom start time in seconds.
+    ///
+    /// Just like [`TOTP::expires_at`], but for codes generated with
+    /// [`TOTP::get_otp_with_custom_time_start`]. If the instant is before
+    /// the start time, the first code expires a full period after the start
+    /// time. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn expires_at_with_start(
+        &self,
+        now: std::time::SystemTime,
+        time_start: u64,
+    ) -> std::time::SystemTime {
+        let time = now
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs()
+            .max(time_start);
+        let expiry = time + self.time_remaining_with_start(time, time_start);
+        std::time::UNIX_EPOCH + Duration::from_secs(expiry)
+    }
+
+    /// Returns the fraction of the current period that has elapsed, from
+    /// 0.0 up to (but excluding) 1.0.
+    ///
+    /// This is useful for animating a countdown, alongside
+    /// [`TOTP::time_remaining`]. It resets to exactly 0.0 at each period
+    /// boundary, when a new code starts being generated.
+    pub fn progress(&self, time: u64) -> f64 {
+        (time.saturating_sub(self.time_start) % self.period) as f64 / self.period as f64
+    }
+
+    /// Returns the time step (the counter fed into the HMAC) for the
+    /// given time in seconds.
+    ///
+    /// This is the time elapsed since the instance's start time divided by
+    /// the period. The start time defaults to the Unix epoch.
+    pub fn counter_for_time(&self, time: u64) -> u64 {
+        self.counter_for_time_with_start(time, self.time_start)
+    }
+
+    /// Returns the time step (the counter fed into the HMAC) for the
+    /// given time and start time in seconds.
+    ///
+    /// If the time is before the start time, the step saturates to 0.
+    pub fn counter_for_time_with_start(&self, time: u64, time_start: u64) -> u64 {
+        time.saturating_sub(time_start) / self.period
     }
 }
 
@@ -177,13 +802,33 @@ impl TOTP {
     /// Generates and returns the TOTP value for the specified time.
     ///
     /// The time must be specified in seconds to calculate the correct
-    /// one-time password.
+    /// one-time password. Time steps are counted from the instance's start
+    /// time, which defaults to 0.
     ///
     /// # Panics
     /// This method panics if the [`TOTP::get_otp_with_custom_time_start`]
     /// method does, which happens if the hash's secret is incorrectly given.
+    /// Use [`TOTP::try_get_otp`] to get an error instead.
     pub fn get_otp(&self, time: u64) -> OTPResult {
-        self.get_otp_with_custom_time_start(time, 0)
+        self.get_otp_with_custom_time_start(time, self.time_start)
+    }
+
+    /// Generates and returns the TOTP value for the specified time, just
+    /// like [`TOTP::get_otp`], but returns an error rather than panicking.
+    ///
+    /// Returns an [`OtpError::InvalidPeriod`] if the period is 0, or an
+    /// [`OtpError::InvalidKey`] if the secret can't be used to key the HMAC.
+    pub fn try_get_otp(&self, time: u64) -> Result<OTPResult, OtpError> {
+        if self.period == 0 {
+            return Err(OtpError::InvalidPeriod);
+        }
+        let time_count = self.counter_for_time(time);
+        let hash =
+            KeyedHmac::try_new(&self.secret, &self.mac_digest)?.hash(&time_count.to_be_bytes());
+        Ok(self
+            .result_from_hash(&hash)
+            .with_counter(time_count)
+            .with_seconds_remaining(self.time_remaining(time)))
     }
 
     /// Generates and returns the TOTP value for the specified time.
@@ -191,21 +836,887 @@ impl TOTP {
     /// The time must be specified in seconds to calculate the correct
     /// one-time password.
     ///
-    /// This method allows a custom start time to be provided.
+    /// This method allows a custom start time to be provided, which is used
+    /// in place of the instance's own. If the time is before the start time,
+    /// the step saturates to 0 and the code for the start time is returned.
     ///
     /// # Panics
     /// This method panics if the hash's secret is incorrectly given.
     pub fn get_otp_with_custom_time_start(&self, time: u64, time_start: u64) -> OTPResult {
-        let time_count = (time - time_start) / self.period;
+        let time_count = self.counter_for_time_with_start(time, time_start);
 
+        self.get_otp_for_step(time_count)
+            .with_seconds_remaining(self.time_remaining_with_start(time, time_start))
+    }
+
+    /// Generates and returns the TOTP value of the period before the
+    /// specified time.
+    ///
+    /// This is the code for `time - period`, which is often still accepted
+    /// to allow for clock drift. Near the epoch, the time saturates to 0
+    /// rather than underflowing.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_previous(&self, time: u64) -> OTPResult {
+        self.get_otp(time.saturating_sub(self.period))
+    }
+
+    /// Generates and returns the TOTP value of the period after the
+    /// specified time.
+    ///
+    /// This is the code for `time + period`, which can be shown ahead of
+    /// time to smoothen the transition between two codes.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_next(&self, time: u64) -> OTPResult {
+        self.get_otp(time.saturating_add(self.period))
+    }
+
+    /// Generates and returns the TOTP value for the given instant.
+    ///
+    /// Instants before the Unix epoch are treated as the epoch itself.
+    /// Requires the `std` feature.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    #[cfg(feature = "std")]
+    pub fn get_otp_at(&self, when: std::time::SystemTime) -> OTPResult {
+        let since_epoch = when
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        self.get_otp_at_duration(since_epoch)
+    }
+
+    /// Generates and returns the TOTP value for the given time elapsed
+    /// since the Unix epoch.
+    ///
+    /// Any fraction of a second is ignored.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_at_duration(&self, since_epoch: Duration) -> OTPResult {
+        self.get_otp(since_epoch.as_secs())
+    }
+
+    /// Generates and returns the TOTP value for a time given in milliseconds
+    /// since the Unix epoch, such as the value of JavaScript's `Date.now()`.
+    ///
+    /// The time is floored to whole seconds. Discarding the sub-second part
+    /// is correct for TOTP, as time steps are at least a second long.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_from_millis(&self, millis: u64) -> OTPResult {
+        self.get_otp(millis / 1000)
+    }
+
+    /// Generates and returns the TOTP value for the current time, as read
+    /// from the given [`Clock`].
+    ///
+    /// This allows a fixed or fake clock to be injected in tests.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_with_clock<C: Clock>(&self, clock: &C) -> OTPResult {
+        self.get_otp(clock.now_unix_secs())
+    }
+
+    /// Generates and returns the TOTP value for the current time, as read
+    /// from the given time source.
+    ///
+    /// Just like [`TOTP::get_otp_with_clock`], but the source can also be a
+    /// trait object such as `&dyn Clock`, so systems with their own trusted
+    /// time can pick it at runtime.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn get_otp_with_source<C: Clock + ?Sized>(&self, src: &C) -> OTPResult {
+        self.get_otp(src.now_unix_secs())
+    }
+
+    /// Generates and returns the TOTP value for the current system time.
+    ///
+    /// This reads the time with a [`SystemClock`], so the current time
+    /// doesn't need to be computed by hand. Requires the `std` feature.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    #[cfg(feature = "std")]
+    pub fn get_otp_now(&self) -> OTPResult {
+        self.get_otp_with_clock(&SystemClock)
+    }
+
+    /// Returns an iterator over the codes generated between two times.
+    ///
+    /// Starting at `start` and stepping by the period, each time before `end`
+    /// is yielded along with its code, as returned by [`TOTP::get_otp`].
+    /// An empty range (`start >= end`) yields nothing, and a range shorter
+    /// than the period yields exactly one code.
+    ///
+    /// # Panics
+    /// Iterating panics if the hash's secret is incorrectly given.
+    pub fn codes_between(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Iterator<Item = (u64, OTPResult)> + '_ {
+        let step = usize::try_from(self.period).unwrap_or(usize::MAX);
+        (start..end)
+            .step_by(step)
+            .map(move |time| (time, self.get_otp(time)))
+    }
+
+    /// Returns the codes accepted by [`TOTP::verify_with_skew`] for the
+    /// specified time and skew.
+    ///
+    /// The codes for the steps from `-skew` to `skew` around the step for the
+    /// time are returned in order, with each step available through
+    /// [`OTPResult::counter`]. This allows callers to do their own set logic,
+    /// such as rejecting codes that were recently used. Near the Unix epoch,
+    /// steps before 0 are left out.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn window_codes(&self, time: u64, skew: u64) -> Vec<OTPResult> {
+        let time_count = self.counter_for_time(time);
+        let session = self.session();
+        (time_count.saturating_sub(skew)..=time_count.saturating_add(skew))
+            .map(|step| session.get_otp_for_step(step))
+            .collect()
+    }
+
+    /// Creates a [`TotpSession`] to generate many codes with the instance.
+    ///
+    /// The session keys the HMAC with the secret once, rather than on every
+    /// generation, which speeds up repeated calls to `get_otp`.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn session(&self) -> TotpSession<'_> {
+        TotpSession {
+            totp: self,
+            hmac: KeyedHmac::new(&self.secret, &self.mac_digest),
+        }
+    }
+
+    /// Generates and returns the TOTP value for an already-computed time step.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    fn get_otp_for_step(&self, time_count: u64) -> OTPResult {
+        let hash = hash_generic(&time_count.to_be_bytes(), &self.secret, &self.mac_digest);
+        self.result_from_hash(&hash).with_counter(time_count)
+    }
+
+    /// Generates and returns the TOTP value for the specified time, using
+    /// a caller-supplied HMAC type instead of the instance's [`MacDigest`].
+    ///
+    /// This allows any [`Mac`] implementation to be used, such as
+    /// `Hmac<Ripemd160>` for legacy tokens. The time must be specified in
+    /// seconds.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given, or if
+    /// the HMAC output is shorter than the 20 bytes needed for truncation.
+    pub fn get_otp_generic<D: Mac + KeyInit>(&self, time: u64) -> OTPResult {
+        let time_count = self.counter_for_time(time);
+        let hash = hash_internal::<D>(&time_count.to_be_bytes(), &self.secret);
+        self.result_from_hash(&hash)
+            .with_counter(time_count)
+            .with_seconds_remaining(self.time_remaining(time))
+    }
+
+    /// Returns the full HMAC output for the specified time, before it's
+    /// truncated into a code.
+    ///
+    /// The time step is computed just like with [`TOTP::get_otp`], and the
+    /// HMAC is as long as the instance's digest output. This is meant for
+    /// comparing against test vectors or other implementations when
+    /// diagnosing why they disagree on a code.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn hmac_for_time(&self, time: u64) -> Vec<u8> {
+        let time_count = self.counter_for_time(time);
+        hash_generic(&time_count.to_be_bytes(), &self.secret, &self.mac_digest).to_vec()
+    }
+
+    /// Generates and returns the TOTP value for the specified time,
+    /// optionally truncating the HMAC at a fixed offset.
+    ///
+    /// With `None`, the dynamic offset from the RFC is used and the code is
+    /// the same as the one given by [`TOTP::get_otp`]. With `Some(n)`, the
+    /// four bytes starting at `n` are used instead, as some legacy tokens
+    /// do. This is only meant for interoperating with such tokens, as codes
+    /// generated with a fixed offset won't match those of any standard
+    /// authenticator or verifier.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given, or if
+    /// the offset leaves fewer than four bytes of the HMAC output (an offset
+    /// above 16 with SHA1).
+    pub fn get_otp_with_offset(&self, time: u64, offset: Option<usize>) -> OTPResult {
+        let time_count = self.counter_for_time(time);
         let hash = hash_generic(&time_count.to_be_bytes(), &self.secret, &self.mac_digest);
-        let offset = (hash[hash.len() - 1] & 0xf) as usize;
-        let bytes: [u8; 4] = hash[offset..offset + 4]
-            .try_into()
-            .expect("Failed byte get");
+        self.result_from_hash_at(&hash, offset)
+            .with_counter(time_count)
+            .with_seconds_remaining(self.time_remaining(time))
+    }
+
+    /// Truncates an HMAC hash into an [`OTPResult`] using the instance's
+    /// digit count and encoding.
+    ///
+    /// # Panics
+    /// This method panics if the hash is too short to be truncated.
+    fn result_from_hash(&self, hash: &[u8]) -> OTPResult {
+        match self.encoding {
+            OtpEncoding::Decimal => dynamic_truncate(hash, self.digits),
+            OtpEncoding::Steam => self.result_from_hash_at(hash, None),
+        }
+    }
+
+    /// Truncates an HMAC hash into an [`OTPResult`] at the given offset, or
+    /// the dynamic offset if none is given.
+    ///
+    /// # Panics
+    /// This method panics if the hash is too short to be truncated.
+    fn result_from_hash_at(&self, hash: &[u8], offset: Option<usize>) -> OTPResult {
+        let bytes = truncate_hash(hash, offset);
+
+        let code = match self.encoding {
+            OtpEncoding::Decimal => get_code(bytes, self.digits),
+            OtpEncoding::Steam => get_truncated_value(bytes),
+        };
+        self.result_for_code(code)
+    }
+
+    /// Wraps a code in an [`OTPResult`] using the instance's encoding.
+    fn result_for_code(&self, code: u32) -> OTPResult {
+        match self.encoding {
+            OtpEncoding::Decimal => OTPResult::new(self.digits, code),
+            OtpEncoding::Steam => {
+                OTPResult::new(STEAM_CODE_LENGTH, code).with_encoding(OtpEncoding::Steam)
+            }
+        }
+    }
+}
 
+/// All otpauth URI methods for the [`TOTP`] struct.
+#[cfg(feature = "std")]
+impl TOTP {
+    /// Generates an otpauth URI for the TOTP instance.
+    ///
+    /// The label is generally the account name, optionally prefixed with the
+    /// issuer and a colon (`Issuer:account`). The issuer parameter is only
+    /// included if one is provided, and the non-standard `t0` parameter only
+    /// if the start time isn't 0. Steam Guard instances include the
+    /// non-standard `encoder=steam` parameter, so their encoding is kept.
+    ///
+    /// The resulting URI can be read back with
+    /// [`parse_otpauth_uri`](crate::util::parse_otpauth_uri).
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>) -> String {
+        let algorithm = self.mac_digest.name();
+
+        let mut params = vec![("secret", base32_encode(&self.secret))];
+        if let Some(issuer) = issuer {
+            params.push(("issuer", issuer.to_string()));
+        }
+        params.push(("algorithm", algorithm.to_string()));
+        params.push(("digits", self.digits.to_string()));
+        params.push(("period", self.period.to_string()));
+        if self.time_start != 0 {
+            params.push(("t0", self.time_start.to_string()));
+        }
+        if self.encoding == OtpEncoding::Steam {
+            params.push(("encoder", String::from("steam")));
+        }
+        for (key, value) in &self.extras {
+            params.push((key, value.clone()));
+        }
+
+        build_otpauth_uri("totp", label, &params)
+    }
+
+    /// Generates an otpauth URI for the TOTP instance using its stored
+    /// issuer and account name.
+    ///
+    /// The label is built as `Issuer:account` when both are known, or just
+    /// the account name otherwise. Colons within the issuer and account name
+    /// are percent-encoded, so parsing the resulting URI with
+    /// [`parse_otpauth_uri`](crate::util::parse_otpauth_uri) gives back the
+    /// same issuer and account name.
+    pub fn to_labeled_uri(&self) -> String {
+        let account = self.account.as_deref().unwrap_or_default();
+        let label = match &self.issuer {
+            Some(issuer) => format!(
+                "{}:{}",
+                escape_label_part(issuer),
+                escape_label_part(account)
+            ),
+            // An empty issuer prefix keeps an encoded colon in the account
+            // name from being read as the separator
+            None if account.contains(':') => format!(":{}", escape_label_part(account)),
+            None => escape_label_part(account),
+        };
+        self.to_uri(&label, self.issuer.as_deref())
+    }
+}
+
+/// All QR code methods for the [`TOTP`] struct.
+#[cfg(feature = "qr")]
+impl TOTP {
+    /// Renders the otpauth URI given by [`TOTP::to_uri`] as a QR code in an
+    /// SVG document, ready to be scanned by an authenticator app.
+    ///
+    /// Requires the `qr` feature.
+    ///
+    /// # Panics
+    /// This method panics if the URI is too long to fit in a QR code, which
+    /// only happens with a secret or label of well over a thousand bytes.
+    pub fn to_qr_svg(&self, label: &str, issuer: Option<&str>) -> String {
+        qr_svg(&self.to_uri(label, issuer))
+    }
+
+    /// Renders the otpauth URI given by [`TOTP::to_uri`] as a QR code in a
+    /// PNG image, returning the encoded image bytes.
+    ///
+    /// Requires the `qr` feature.
+    ///
+    /// # Panics
+    /// This method panics if the URI is too long to fit in a QR code, which
+    /// only happens with a secret or label of well over a thousand bytes.
+    pub fn to_qr_png(&self, label: &str, issuer: Option<&str>) -> Vec<u8> {
+        qr_png(&self.to_uri(label, issuer))
+    }
+}
+
+/// All binary serialization methods for the [`TOTP`] struct.
+impl TOTP {
+    /// Serializes the TOTP instance into a compact binary layout.
+    ///
+    /// The layout is a version byte (currently 1), followed by:
+    /// - the digest, as a single byte tag
+    /// - the encoding, as 0 for [`OtpEncoding::Decimal`] or 1 for
+    ///   [`OtpEncoding::Steam`]
+    /// - the digit count, as a single byte
+    /// - the period and start time, as big-endian `u64`s
+    /// - the secret's length, as a big-endian `u32`, then the secret
+    ///
+    /// The issuer, account name and extra URI parameters aren't included, as
+    /// they don't affect the generated codes. Use [`TOTP::from_bytes`] to
+    /// read the instance back.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let encoding = match self.encoding {
+            OtpEncoding::Decimal => 0,
+            OtpEncoding::Steam => 1,
+        };
+        let mut bytes = Vec::from([
+            BYTES_VERSION,
+            self.mac_digest.to_tag(),
+            encoding,
+            digits_byte(self.digits),
+        ]);
+        bytes.extend_from_slice(&self.period.to_be_bytes());
+        bytes.extend_from_slice(&self.time_start.to_be_bytes());
+        write_secret(&mut bytes, &self.secret);
+        bytes
+    }
+
+    /// Reads a TOTP instance written by [`TOTP::to_bytes`].
+    ///
+    /// Returns a [`ParseError::InvalidBytes`] if the data is truncated, has
+    /// trailing bytes, or uses an unknown version or encoding, and a
+    /// [`ParseError::UnknownAlgorithm`] for an unknown digest. The secret,
+    /// digit count and period are then validated just like
+    /// [`TOTP::try_new`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let mut reader = ByteReader::new(bytes)?;
+        let mac_digest = MacDigest::from_tag(reader.read_u8()?)?;
+        let encoding = match reader.read_u8()? {
+            0 => OtpEncoding::Decimal,
+            1 => OtpEncoding::Steam,
+            _ => return Err(ParseError::InvalidBytes),
+        };
+        let digits = reader.read_u8()? as u32;
+        let period = reader.read_u64()?;
+        let time_start = reader.read_u64()?;
+        let secret = reader.read_secret()?;
+        reader.finish()?;
+
+        Ok(TOTP::try_new(secret, mac_digest, digits, period)?
+            .with_encoding(encoding)
+            .with_time_start(time_start))
+    }
+}
+
+/// All otp verification methods for the [`TOTP`] struct.
+impl TOTP {
+    /// Verifies a code against the TOTP value for the specified time.
+    ///
+    /// The code is compared against the numerical representation given by
+    /// [`OTPResult::as_u32`], so leading zeroes don't need to be accounted
+    /// for by the caller. The time must be specified in seconds.
+    ///
+    /// Both codes are compared in their zero-padded string form in constant
+    /// time to avoid leaking timing information.
+    ///
+    /// # Panics
+    /// This method panics if the [`TOTP::get_otp`] method does, which
+    /// happens if the hash's secret is incorrectly given.
+    pub fn verify(&self, code: u32, time: u64) -> bool {
+        self.verify_with_skew(code, time, 0)
+    }
+
+    /// Verifies a code against the TOTP values within a window of time steps.
+    ///
+    /// Along with the step for the specified time, the `skew` steps before
+    /// and after it are also checked. For example, a skew of 1 accepts the
+    /// codes for `time - period`, `time` and `time + period`. A skew of 0 is
+    /// the same as an exact match with [`TOTP::verify`].
+    ///
+    /// Allowing some skew accounts for clock drift between the client and
+    /// server, but every extra step widens the window in which a code is
+    /// accepted and so weakens the security of the check. Keep it as small
+    /// as possible (generally no more than 1).
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_with_skew(&self, code: u32, time: u64, skew: u64) -> bool {
+        self.verify_detailed(code, time, skew).is_valid()
+    }
+
+    /// Verifies a code within a window of time steps, returning whether it
+    /// matched exactly, within the skew, or not at all.
+    ///
+    /// This checks the same steps as [`TOTP::verify_with_skew`], but a match
+    /// outside the current step is reported as
+    /// [`VerifyResult::ValidWithinSkew`] along with its offset, so a server
+    /// can log the drift.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_detailed(&self, code: u32, time: u64, skew: u64) -> VerifyResult {
+        match self.verify_returning_offset(code, time, skew) {
+            Some(0) => VerifyResult::Valid,
+            Some(offset) => VerifyResult::ValidWithinSkew(offset),
+            None => VerifyResult::Invalid,
+        }
+    }
+
+    /// Verifies a code within a window of time steps, returning the offset
+    /// of the step that matched.
+    ///
+    /// The offset is the signed number of periods between the step for the
+    /// specified time and the step that produced the code, so a server can
+    /// persist the observed drift. `None` is returned if no step within
+    /// `skew` matched.
+    ///
+    /// Steps are checked outward from the current one (0, -1, 1, -2, 2...),
+    /// so the offset closest to zero is returned if multiple steps match.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_returning_offset(&self, code: u32, time: u64, skew: u64) -> Option<i64> {
+        self.find_step(&self.result_for_code(code).as_string(), time, skew, None)
+            .map(|(_, offset)| offset)
+    }
+
+    /// Verifies a code within a window of time steps, accepting each step
+    /// only once.
+    ///
+    /// To stop a code from being replayed while it's still valid, the step
+    /// of the last code accepted for the user is passed in, and only later
+    /// steps are checked. The matched step is returned for the caller to
+    /// persist and pass in on the next attempt, while `None` is returned if
+    /// no later step within `skew` matched. Pass `None` as the last accepted
+    /// step if no code has been accepted yet.
+    ///
+    /// The `skew` works just like it does with [`TOTP::verify_with_skew`],
+    /// and codes are also compared in constant time.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_once(
+        &self,
+        code: u32,
+        time: u64,
+        skew: u64,
+        last_accepted_step: Option<u64>,
+    ) -> Option<u64> {
+        let expected = self.result_for_code(code).as_string();
+        self.find_step(&expected, time, skew, last_accepted_step)
+            .map(|(step, _)| step)
+    }
+
+    /// Finds the step within `skew` steps of the specified time whose
+    /// string-formatted code matches the given one, returning it along with
+    /// its offset.
+    ///
+    /// Steps up to and including `after` are skipped, if given.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    fn find_step(
+        &self,
+        expected: &str,
+        time: u64,
+        skew: u64,
+        after: Option<u64>,
+    ) -> Option<(u64, i64)> {
+        let matches = |step: u64| {
+            after.map_or(true, |after| step > after)
+                && self.get_otp_for_step(step).matches(expected)
+        };
+
+        let time_count = self.counter_for_time(time);
+        if matches(time_count) {
+            return Some((time_count, 0));
+        }
+
+        for distance in 1..=skew {
+            let candidates = [
+                (time_count.checked_sub(distance), -(distance as i64)),
+                (time_count.checked_add(distance), distance as i64),
+            ];
+            for (step, offset) in candidates {
+                match step {
+                    Some(step) if matches(step) => return Some((step, offset)),
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Verifies a string-formatted code against the TOTP values within a
+    /// window of time steps.
+    ///
+    /// The code is compared against the zero-padded string given by
+    /// [`OTPResult::as_string`], so leading zeroes are significant, and
+    /// Steam Guard codes are checked with [`OtpEncoding::Steam`]. Surrounding
+    /// whitespace is trimmed, and a code that isn't exactly as long as the
+    /// generated codes is rejected without being checked.
+    ///
+    /// The `skew` works just like it does with [`TOTP::verify_with_skew`],
+    /// and codes are also compared in constant time.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_str(&self, code: &str, time: u64, skew: u64) -> bool {
+        let code = code.trim();
+        let length = match self.encoding {
+            OtpEncoding::Decimal => self.digits,
+            OtpEncoding::Steam => STEAM_CODE_LENGTH,
+        };
+        if code.len() != length as usize {
+            return false;
+        }
+        self.find_step(code, time, skew, None).is_some()
+    }
+
+    /// Verifies a code already wrapped in an [`OTPResult`] against the TOTP
+    /// values within a window of time steps.
+    ///
+    /// A candidate whose digit count or encoding differs from the generated
+    /// codes is rejected without being checked. Otherwise, it's rendered with
+    /// [`OTPResult::as_string`] and compared just like with
+    /// [`TOTP::verify_str`], in constant time.
+    ///
+    /// # Panics
+    /// This method panics if the hash's secret is incorrectly given.
+    pub fn verify_result(&self, candidate: &OTPResult, time: u64, skew: u64) -> bool {
+        let expected = self.result_for_code(0);
+        if candidate.get_digits() != expected.get_digits()
+            || candidate.get_encoding() != expected.get_encoding()
+        {
+            return false;
+        }
+        self.find_step(&candidate.as_string(), time, skew, None)
+            .is_some()
+    }
+}
+
+/// The outcome of verifying a code with [`TOTP::verify_detailed`].
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum VerifyResult {
+    /// The code matched the step for the specified time.
+    Valid,
+    /// The code matched a step within the skew, offset by the given signed
+    /// number of periods from the step for the specified time.
+    ValidWithinSkew(i64),
+    /// The code didn't match any step within the skew.
+    Invalid,
+}
+
+impl VerifyResult {
+    /// Returns whether the code matched, either exactly or within the skew.
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, VerifyResult::Invalid)
+    }
+}
+
+/// A Display implementation for the [`TOTP`] struct
+///
+/// Returns a concise summary of the parameters, such as
+/// `TOTP(SHA1, 6 digits, 30s)`, which never includes the secret. Steam
+/// Guard instances show the encoding instead of the digit count, such as
+/// `TOTP(SHA1, Steam, 30s)`.
+impl fmt::Display for TOTP {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.encoding {
+            OtpEncoding::Decimal => write!(
+                f,
+                "TOTP({}, {} digits, {}s)",
+                self.mac_digest, self.digits, self.period
+            ),
+            OtpEncoding::Steam => write!(f, "TOTP({}, Steam, {}s)", self.mac_digest, self.period),
+        }
+    }
+}
+
+/// A Debug implementation for the [`TOTP`] struct
+///
+/// The secret is redacted so that logging an instance doesn't leak the key,
+/// with only its length shown (e.g. `secret: [REDACTED; 20 bytes]`).
+impl fmt::Debug for TOTP {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TOTP")
+            .field("secret", &RedactedSecret(self.secret.len()))
+            .field("mac_digest", &self.mac_digest)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("time_start", &self.time_start)
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("encoding", &self.encoding)
+            .field("extras", &self.extras)
+            .finish()
+    }
+}
+
+/// Zeroes out the secret when the [`TOTP`] struct is dropped.
+#[cfg(feature = "zeroize")]
+impl Drop for TOTP {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for TOTP {}
+
+/// Generates arbitrary TOTP instances for fuzzing, with the `arbitrary`
+/// feature.
+///
+/// Just like with [`TOTP::try_new`], the secret is never empty, the digit
+/// count is between 1 and 10 and the period is at least 1 second.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for TOTP {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let secret = arbitrary_secret(u)?;
+        let mac_digest = u.arbitrary()?;
+        let digits = u.int_in_range(DIGITS_RANGE)?;
+        let period = u.int_in_range(1..=u64::MAX)?;
+        let mut totp = TOTP::from_secret_vec(secret, mac_digest, digits, period);
+        totp.issuer = u.arbitrary()?;
+        totp.account = u.arbitrary()?;
+        Ok(totp)
+    }
+}
+
+/// The parameters of a [`TOTP`] other than its secret, passed to
+/// [`TOTP::from_config`].
+///
+/// The default is the same as the `default_from_*` constructors:
+/// [`MacDigest::SHA1`], [`DEFAULT_DIGITS`], [`DEFAULT_PERIOD`] and a start
+/// time of 0. With the `serde` feature, missing fields are filled in with
+/// these defaults when deserializing, so a config can be read from a
+/// settings file.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TotpConfig {
+    /// The digest to use in the HMAC process.
+    pub mac_digest: MacDigest,
+    /// The number of digits of the generated code.
+    pub digits: u32,
+    /// The period in seconds between two different generated codes.
+    pub period: u64,
+    /// The start time (T0) in seconds that time steps are counted from.
+    pub time_start: u64,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        TotpConfig {
+            mac_digest: MacDigest::SHA1,
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            time_start: 0,
+        }
+    }
+}
+
+/// A builder for the [`TOTP`] struct, created with [`TOTP::builder`].
+///
+/// The secret must be set with [`TotpBuilder::secret_bytes`] or
+/// [`TotpBuilder::secret_base32`], while all other fields are optional.
+/// The digit count and period are validated by [`TotpBuilder::build`].
+///
+/// # Example
+/// ```rust
+/// use xotp::totp::TOTP;
+/// use xotp::util::MacDigest;
+///
+/// let totp = TOTP::builder()
+///     .secret_bytes(b"12345678901234567890")
+///     .digest(MacDigest::SHA256)
+///     .digits(8)
+///     .issuer("ACME Co")
+///     .build()
+///     .unwrap();
+/// assert_eq!(totp.get_period(), 30);
+/// ```
+pub struct TotpBuilder {
+    secret: Result<Vec<u8>, ParseError>,
+    mac_digest: MacDigest,
+    digits: u32,
+    period: u64,
+    time_start: u64,
+    issuer: Option<String>,
+    account: Option<String>,
+}
+
+/// Creates a builder with no secret, [`MacDigest::SHA1`],
+/// [`DEFAULT_DIGITS`] and [`DEFAULT_PERIOD`].
+impl Default for TotpBuilder {
+    fn default() -> Self {
+        TotpBuilder {
+            secret: Err(ParseError::MissingSecret),
+            mac_digest: MacDigest::SHA1,
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+            time_start: 0,
+            issuer: None,
+            account: None,
+        }
+    }
+}
+
+impl TotpBuilder {
+    /// Sets the secret from its byte-array representation.
+    pub fn secret_bytes(mut self, secret: &[u8]) -> Self {
+        self.secret = Ok(secret.to_vec());
+        self
+    }
+
+    /// Sets the secret from its base32 representation.
+    ///
+    /// If the string isn't correctly base32-encoded,
+    /// [`TotpBuilder::build`] returns a [`ParseError::SecretParsingError`].
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn secret_base32(mut self, secret: &str) -> Self {
+        self.secret = base32_decode(secret)
+            .ok_or_else(|| ParseError::SecretParsingError(String::from(secret)));
+        self
+    }
+
+    /// Sets the digest to use in the HMAC process.
+    pub fn digest(mut self, mac_digest: MacDigest) -> Self {
+        self.mac_digest = mac_digest;
+        self
+    }
+
+    /// Sets the number of digits of the generated code.
+    pub fn digits(mut self, digits: u32) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// Sets the period in seconds between two different generated codes.
+    pub fn period(mut self, period: u64) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Sets the start time (T0) in seconds that time steps are counted from.
+    pub fn time_start(mut self, time_start: u64) -> Self {
+        self.time_start = time_start;
+        self
+    }
+
+    /// Sets the issuer of the account the TOTP belongs to.
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.issuer = Some(String::from(issuer));
+        self
+    }
+
+    /// Sets the name of the account the TOTP belongs to.
+    pub fn account(mut self, account: &str) -> Self {
+        self.account = Some(String::from(account));
+        self
+    }
+
+    /// Builds the TOTP instance.
+    ///
+    /// Returns a [`ParseError::MissingSecret`] if no secret was set, and
+    /// otherwise validates the secret, digit count and period just like
+    /// [`TOTP::try_new`].
+    pub fn build(self) -> Result<TOTP, ParseError> {
+        let secret = self.secret?;
+        TOTP::check_params(&secret, self.digits, self.period)?;
+        Ok(TOTP {
+            secret,
+            mac_digest: self.mac_digest,
+            digits: self.digits,
+            period: self.period,
+            time_start: self.time_start,
+            issuer: self.issuer,
+            account: self.account,
+            encoding: OtpEncoding::Decimal,
+            extras: BTreeMap::new(),
+        })
+    }
+}
+
+/// A TOTP generator with a precomputed HMAC key, created with
+/// [`TOTP::session`].
+///
+/// Generating a code with [`TOTP::get_otp`] keys a new HMAC with the secret
+/// every time. A session keys it once and reuses it, which is faster when
+/// generating codes repeatedly, such as for a token refreshing every second
+/// or when checking many time steps. The codes are the same as those of the
+/// [`TOTP`] instance it was created from.
+pub struct TotpSession<'a> {
+    totp: &'a TOTP,
+    hmac: KeyedHmac,
+}
+
+impl TotpSession<'_> {
+    /// Generates and returns the TOTP value for the specified time, just
+    /// like [`TOTP::get_otp`].
+    pub fn get_otp(&self, time: u64) -> OTPResult {
+        self.get_otp_with_custom_time_start(time, self.totp.time_start)
+    }
+
+    /// Generates and returns the TOTP value for the specified time and
+    /// start time, just like [`TOTP::get_otp_with_custom_time_start`].
+    pub fn get_otp_with_custom_time_start(&self, time: u64, time_start: u64) -> OTPResult {
+        let time_count = self.totp.counter_for_time_with_start(time, time_start);
+
+        self.get_otp_for_step(time_count)
+            .with_seconds_remaining(self.totp.time_remaining_with_start(time, time_start))
+    }
 
-        let code = get_code(bytes, self.digits);
-        OTPResult::new(self.digits, code)
+    /// Generates and returns the TOTP value for an already-computed time step.
+    fn get_otp_for_step(&self, time_count: u64) -> OTPResult {
+        let hash = self.hmac.hash(&time_count.to_be_bytes());
+        self.totp.result_from_hash(&hash).with_counter(time_count)
     }
 }
@@ -1,12 +1,25 @@
-use base32::Alphabet;
+#[cfg(feature = "migration")]
+pub use crate::migration::{build_migration_uri, parse_migration_uri};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+use core::ops::{Deref, RangeInclusive};
+use core::str::FromStr;
+use hmac::digest::Output;
 use hmac::{Hmac, Mac};
 use sha1::Sha1;
-use sha2::{Sha256, Sha512};
-use std::collections::HashMap;
-use url::Url;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+#[cfg(feature = "zeroize")]
+use zeroize::{zeroize_flat_type, Zeroize, ZeroizeOnDrop};
+#[cfg(feature = "std")]
+use {base32::Alphabet, percent_encoding::percent_decode_str, std::collections::HashMap, url::Url};
 
 use crate::hotp::HOTP;
-use crate::totp::TOTP;
+use crate::otp_result::OTPResult;
+use crate::totp::{TotpSession, TOTP};
 
 /// The digest to use with TOTP.
 ///
@@ -18,24 +31,327 @@ use crate::totp::TOTP;
 /// SHA1 is still primarily used, and some other authenticator applications
 /// may not support other digest algorithms.
 ///
+/// The truncated SHA224 and SHA384 used by some deployments are also
+/// supported, and SHA3-256 and SHA3-512 are additionally supported with the
+/// `sha3` feature. The feature-gated variants come last, so enabling it
+/// doesn't shift the position of the other ones.
+///
 /// [RFC6238]: https://datatracker.ietf.org/doc/html/rfc6238
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum MacDigest {
     SHA1,
     SHA256,
     SHA512,
+    SHA224,
+    SHA384,
+    #[cfg(feature = "sha3")]
+    SHA3_256,
+    #[cfg(feature = "sha3")]
+    SHA3_512,
+}
+
+/// Size parameters of the [`MacDigest`] enum.
+impl MacDigest {
+    /// Returns the size in bytes of the digest's output, which is also the
+    /// size of the HMAC generated with it.
+    pub fn output_bytes(&self) -> usize {
+        match self {
+            MacDigest::SHA1 => 20,
+            MacDigest::SHA256 => 32,
+            MacDigest::SHA512 => 64,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_256 => 32,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_512 => 64,
+            MacDigest::SHA224 => 28,
+            MacDigest::SHA384 => 48,
+        }
+    }
+
+    /// Returns the block size in bytes of the digest.
+    ///
+    /// HMAC keys longer than this are hashed down first, so it's the largest
+    /// secret length that adds to the key's strength.
+    pub fn block_size(&self) -> usize {
+        match self {
+            MacDigest::SHA1 => 64,
+            MacDigest::SHA256 => 64,
+            MacDigest::SHA512 => 128,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_256 => 136,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_512 => 72,
+            MacDigest::SHA224 => 64,
+            MacDigest::SHA384 => 128,
+        }
+    }
+}
+
+/// Name getters for the [`MacDigest`] enum.
+impl MacDigest {
+    /// Returns the canonical name of the digest, as used for the `algorithm`
+    /// parameter of an otpauth URI (such as `"SHA1"` or `"SHA3-256"`).
+    ///
+    /// The name can be parsed back with [`MacDigest::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            MacDigest::SHA1 => "SHA1",
+            MacDigest::SHA256 => "SHA256",
+            MacDigest::SHA512 => "SHA512",
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_256 => "SHA3-256",
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_512 => "SHA3-512",
+            MacDigest::SHA224 => "SHA224",
+            MacDigest::SHA384 => "SHA384",
+        }
+    }
+}
+
+/// A Display implementation for the [`MacDigest`] enum
+///
+/// Returns the canonical name of the digest, given by [`MacDigest::name`].
+impl fmt::Display for MacDigest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Binary tags for the [`MacDigest`] enum.
+impl MacDigest {
+    /// Gets the tag the digest is written as by [`TOTP::to_bytes`].
+    pub(crate) fn to_tag(self) -> u8 {
+        match self {
+            MacDigest::SHA1 => 0,
+            MacDigest::SHA256 => 1,
+            MacDigest::SHA512 => 2,
+            MacDigest::SHA224 => 3,
+            MacDigest::SHA384 => 4,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_256 => 5,
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_512 => 6,
+        }
+    }
+
+    /// Reads a digest from its tag, returning a
+    /// [`ParseError::UnknownAlgorithm`] for unknown tags, or for SHA3 tags
+    /// without the `sha3` feature.
+    pub(crate) fn from_tag(tag: u8) -> Result<Self, ParseError> {
+        match tag {
+            0 => Ok(MacDigest::SHA1),
+            1 => Ok(MacDigest::SHA256),
+            2 => Ok(MacDigest::SHA512),
+            3 => Ok(MacDigest::SHA224),
+            4 => Ok(MacDigest::SHA384),
+            #[cfg(feature = "sha3")]
+            5 => Ok(MacDigest::SHA3_256),
+            #[cfg(feature = "sha3")]
+            6 => Ok(MacDigest::SHA3_512),
+            x => Err(ParseError::UnknownAlgorithm(x.to_string())),
+        }
+    }
+}
+
+/// Parses a digest from its name, such as the `algorithm` parameter of an
+/// otpauth URI.
+///
+/// Names are case-insensitive, and the hyphenated forms (such as `SHA-256`)
+/// are accepted alongside the canonical ones. Unknown names return a
+/// [`ParseError::UnknownAlgorithm`].
+impl FromStr for MacDigest {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "SHA1" | "SHA-1" => Ok(MacDigest::SHA1),
+            "SHA256" | "SHA-256" => Ok(MacDigest::SHA256),
+            "SHA512" | "SHA-512" => Ok(MacDigest::SHA512),
+            "SHA224" | "SHA-224" => Ok(MacDigest::SHA224),
+            "SHA384" | "SHA-384" => Ok(MacDigest::SHA384),
+            #[cfg(feature = "sha3")]
+            "SHA3-256" | "SHA3_256" => Ok(MacDigest::SHA3_256),
+            #[cfg(feature = "sha3")]
+            "SHA3-512" | "SHA3_512" => Ok(MacDigest::SHA3_512),
+            _ => Err(ParseError::UnknownAlgorithm(String::from(s))),
+        }
+    }
+}
+
+/// The encoding used to render a generated OTP.
+///
+/// Standard [H/T]OTPs are rendered as decimal digits, as specified in
+/// [RFC4226]. Steam Guard instead maps the truncated value into a 5-character
+/// alphanumeric code, which is supported with [`OtpEncoding::Steam`].
+///
+/// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OtpEncoding {
+    #[default]
+    Decimal,
+    Steam,
+}
+
+/// Name getters for the [`OtpEncoding`] enum.
+impl OtpEncoding {
+    /// Returns the display name of the encoding (`"Decimal"` or `"Steam"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            OtpEncoding::Decimal => "Decimal",
+            OtpEncoding::Steam => "Steam",
+        }
+    }
+}
+
+/// A Display implementation for the [`OtpEncoding`] enum
+///
+/// Returns the display name of the encoding, given by [`OtpEncoding::name`].
+impl fmt::Display for OtpEncoding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The digit counts a decimal code can be generated with.
+///
+/// The truncated value is at most 31 bits, so a code can't have more than
+/// 10 digits.
+pub(crate) const DIGITS_RANGE: RangeInclusive<u32> = 1..=10;
+
+/// The minimum secret length in bytes accepted by the `try_new_checked`
+/// constructors.
+///
+/// [RFC4226] requires secrets of at least 128 bits, and recommends 160 bits.
+///
+/// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226#section-4
+pub const MIN_SECRET_BYTES: usize = 16;
+
+/// Checks a secret isn't empty, which would generate meaningless codes.
+pub(crate) fn check_secret_not_empty(secret: &[u8]) -> Result<(), ParseError> {
+    if secret.is_empty() {
+        return Err(ParseError::EmptySecret);
+    }
+    Ok(())
+}
+
+/// Checks a secret is at least [`MIN_SECRET_BYTES`] long.
+pub(crate) fn check_secret_len(secret: &[u8]) -> Result<(), ParseError> {
+    if secret.len() < MIN_SECRET_BYTES {
+        return Err(ParseError::SecretTooShort(secret.len()));
+    }
+    Ok(())
+}
+
+/// The alphabet Steam Guard codes are made up of.
+const STEAM_ALPHABET: &[u8] = b"23456789BCDFGHJKMNPQRTVWXY";
+
+/// The length of a Steam Guard code.
+pub(crate) const STEAM_CODE_LENGTH: u32 = 5;
+
+/// A source of the current time, used to generate time-based OTPs.
+///
+/// [`TOTP::get_otp_with_clock`] reads the time from a [`Clock`], so a fixed
+/// or fake clock can be injected in tests and other deterministic
+/// environments, and a trusted external time (e.g. a GPS receiver or an
+/// NTP-disciplined clock) can be plugged in. [`SystemClock`] reads the
+/// system time.
+pub trait Clock {
+    /// Gets the current time in seconds since the Unix epoch.
+    fn now_unix_secs(&self) -> u64;
+}
+
+/// A [`Clock`] reading the system time.
+///
+/// Times before the Unix epoch are read as the epoch itself.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now_unix_secs(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    }
+}
+
+/// Picks the four bytes of an HMAC hash to truncate into a code.
+///
+/// With no offset given, the dynamic offset from [RFC4226] is used, read
+/// from the low four bits of the last byte. A fixed offset can be given for
+/// legacy tokens that don't follow the RFC.
+///
+/// # Panics
+/// This method panics if the hash has fewer than four bytes from the offset.
+///
+/// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226#section-5.3
+pub(crate) fn truncate_hash(hash: &[u8], offset: Option<usize>) -> [u8; 4] {
+    let offset = offset.unwrap_or((hash[hash.len() - 1] & 0xf) as usize);
+    hash.get(offset..)
+        .and_then(|bytes| bytes.get(..4))
+        .and_then(|bytes| bytes.try_into().ok())
+        .expect("Truncation offset out of range")
+}
+
+/// Truncates an HMAC hash into a decimal code of the given digit count.
+///
+/// This is the dynamic truncation step from [RFC4226]: four bytes are read
+/// from the offset given by the low four bits of the hash's last byte, and
+/// the resulting 31-bit value is reduced to `digits` decimal digits. Both
+/// [`HOTP`] and [`TOTP`] generate their codes with it, and it can be
+/// applied to the output of any other HMAC.
+///
+/// # Panics
+/// This method panics if the hash has fewer than four bytes from the
+/// offset, which can't happen with at least 19 bytes of hash.
+///
+/// [RFC4226]: https://datatracker.ietf.org/doc/html/rfc4226#section-5.3
+pub fn dynamic_truncate(hash: &[u8], digits: u32) -> OTPResult {
+    OTPResult::new(digits, get_code(truncate_hash(hash, None), digits))
+}
+
+/// A generic method to convert the [H/T]OTP byte-array into its truncated
+/// 31-bit value, before any encoding is applied.
+pub(crate) fn get_truncated_value(bytes: [u8; 4]) -> u32 {
+    (((bytes[0] & 0x7f) as u32) << 24)
+        | ((bytes[1] as u32) << 16)
+        | ((bytes[2] as u32) << 8)
+        | bytes[3] as u32
 }
 
 /// A generic method to convert the [H/T]OTP byte-array into the
 /// requested decimal-based code.
 ///
 /// Needs the bytes to convert and the amount of digits the code should be.
+///
+/// The modulus is computed as a `u64` so 10-digit codes don't overflow.
+/// As the truncated value is at most 31 bits (10 decimal digits), any digit
+/// count above 10 returns the full value.
 pub(crate) fn get_code(bytes: [u8; 4], digits: u32) -> u32 {
-    let code = (((bytes[0] & 0x7f) as u32) << 24)
-        | ((bytes[1] as u32) << 16)
-        | ((bytes[2] as u32) << 8)
-        | bytes[3] as u32;
-    code % (10_u32.pow(digits))
+    let modulus = 10_u64.pow(digits.min(10));
+    (get_truncated_value(bytes) as u64 % modulus) as u32
+}
+
+/// Renders a truncated value as a Steam Guard code of the given length.
+///
+/// Each character is picked from the Steam alphabet using the remainder of
+/// the value, least-significant first.
+pub(crate) fn encode_steam(code: u32, length: u32) -> String {
+    let base = STEAM_ALPHABET.len() as u32;
+    let mut code = code;
+    (0..length)
+        .map(|_| {
+            let c = STEAM_ALPHABET[(code % base) as usize] as char;
+            code /= base;
+            c
+        })
+        .collect()
 }
 
 /// A method to hash a message with a given secret and digest.
@@ -43,34 +359,531 @@ pub(crate) fn get_code(bytes: [u8; 4], digits: u32) -> u32 {
 /// The only time [`MacDigest`] is not [`MacDigest::SHA1`] is when the
 /// TOTP instance's mac_digest is set otherwise.
 ///
-/// Calls the underlying [`hash_internal`] function with the correctly
-/// HMAC-mapped algorithm.
-pub(crate) fn hash_generic(msg: &[u8], secret: &[u8], digest: &MacDigest) -> Vec<u8> {
-    match *digest {
-        MacDigest::SHA1 => hash_internal::<Hmac<Sha1>>(msg, secret),
-        MacDigest::SHA256 => hash_internal::<Hmac<Sha256>>(msg, secret),
-        MacDigest::SHA512 => hash_internal::<Hmac<Sha512>>(msg, secret),
+/// Keys a [`KeyedHmac`] with the correctly HMAC-mapped algorithm and hashes
+/// the message with it. The hash is returned on the stack, so no
+/// allocation is made.
+pub(crate) fn hash_generic(msg: &[u8], secret: &[u8], digest: &MacDigest) -> HashOutput {
+    KeyedHmac::new(secret, digest).hash(msg)
+}
+
+/// Hashes a message with a given secret and a digest selected by name.
+///
+/// The name is parsed just like [`MacDigest::from_str`], so it's
+/// case-insensitive and accepts the hyphenated forms. This is useful when
+/// the algorithm is only known at runtime, such as when read from a config
+/// file. Unknown names return a [`ParseError::UnknownAlgorithm`].
+pub fn hash_with_name(msg: &[u8], secret: &[u8], algo: &str) -> Result<Vec<u8>, ParseError> {
+    let digest = algo.parse::<MacDigest>()?;
+    Ok(hash_generic(msg, secret, &digest).to_vec())
+}
+
+/// An HMAC of any [`MacDigest`], keyed with a secret ahead of time.
+///
+/// Keying the HMAC pads and hashes the secret, so a [`KeyedHmac`] can be
+/// kept around to skip that work when hashing many messages with the same
+/// secret. Each message is hashed with a clone of the keyed state.
+#[derive(Clone)]
+pub(crate) enum KeyedHmac {
+    SHA1(Hmac<Sha1>),
+    SHA256(Hmac<Sha256>),
+    SHA512(Hmac<Sha512>),
+    #[cfg(feature = "sha3")]
+    SHA3_256(Hmac<sha3::Sha3_256>),
+    #[cfg(feature = "sha3")]
+    SHA3_512(Hmac<sha3::Sha3_512>),
+    SHA224(Hmac<Sha224>),
+    SHA384(Hmac<Sha384>),
+}
+
+impl KeyedHmac {
+    /// Keys an HMAC of the given digest with the secret.
+    ///
+    /// # Panics
+    /// This method panics if the provided secret is invalid and the HMAC
+    /// cannot be keyed.
+    pub(crate) fn new(secret: &[u8], digest: &MacDigest) -> Self {
+        KeyedHmac::try_new(secret, digest).expect("Failed to initialize HMAC")
+    }
+
+    /// Keys an HMAC of the given digest with the secret, returning an
+    /// [`OtpError::InvalidKey`] if the HMAC cannot be keyed.
+    pub(crate) fn try_new(secret: &[u8], digest: &MacDigest) -> Result<Self, OtpError> {
+        Ok(match *digest {
+            MacDigest::SHA1 => KeyedHmac::SHA1(try_new_keyed(secret)?),
+            MacDigest::SHA256 => KeyedHmac::SHA256(try_new_keyed(secret)?),
+            MacDigest::SHA512 => KeyedHmac::SHA512(try_new_keyed(secret)?),
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_256 => KeyedHmac::SHA3_256(try_new_keyed(secret)?),
+            #[cfg(feature = "sha3")]
+            MacDigest::SHA3_512 => KeyedHmac::SHA3_512(try_new_keyed(secret)?),
+            MacDigest::SHA224 => KeyedHmac::SHA224(try_new_keyed(secret)?),
+            MacDigest::SHA384 => KeyedHmac::SHA384(try_new_keyed(secret)?),
+        })
+    }
+
+    /// Hashes a message, leaving the keyed state untouched.
+    pub(crate) fn hash(&self, msg: &[u8]) -> HashOutput {
+        match self {
+            KeyedHmac::SHA1(hmac) => hash_keyed(hmac, msg),
+            KeyedHmac::SHA256(hmac) => hash_keyed(hmac, msg),
+            KeyedHmac::SHA512(hmac) => hash_keyed(hmac, msg),
+            #[cfg(feature = "sha3")]
+            KeyedHmac::SHA3_256(hmac) => hash_keyed(hmac, msg),
+            #[cfg(feature = "sha3")]
+            KeyedHmac::SHA3_512(hmac) => hash_keyed(hmac, msg),
+            KeyedHmac::SHA224(hmac) => hash_keyed(hmac, msg),
+            KeyedHmac::SHA384(hmac) => hash_keyed(hmac, msg),
+        }
     }
 }
 
+/// Zeroes out the keyed state, which is derived from the secret, when the
+/// [`KeyedHmac`] enum is dropped.
+#[cfg(feature = "zeroize")]
+impl Drop for KeyedHmac {
+    fn drop(&mut self) {
+        // SAFETY: the HMAC states of the supported digests only hold
+        // fixed-size arrays and integers without Drop impls, for which all
+        // zeroes is a valid bit pattern. The enum tag itself is left as is.
+        unsafe {
+            match self {
+                KeyedHmac::SHA1(hmac) => zeroize_flat_type(hmac),
+                KeyedHmac::SHA256(hmac) => zeroize_flat_type(hmac),
+                KeyedHmac::SHA512(hmac) => zeroize_flat_type(hmac),
+                #[cfg(feature = "sha3")]
+                KeyedHmac::SHA3_256(hmac) => zeroize_flat_type(hmac),
+                #[cfg(feature = "sha3")]
+                KeyedHmac::SHA3_512(hmac) => zeroize_flat_type(hmac),
+                KeyedHmac::SHA224(hmac) => zeroize_flat_type(hmac),
+                KeyedHmac::SHA384(hmac) => zeroize_flat_type(hmac),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for KeyedHmac {}
+
+/// Keys an HMAC of the given type with the secret.
+///
+/// # Panics
+/// The method will panic if the provided secret is invalid and the HMAC
+/// cannot be keyed.
+fn new_keyed<D: Mac + hmac::digest::KeyInit>(secret: &[u8]) -> D {
+    try_new_keyed(secret).expect("Failed to initialize HMAC")
+}
+
+/// Keys an HMAC of the given type with the secret, returning an
+/// [`OtpError::InvalidKey`] if the HMAC cannot be keyed.
+fn try_new_keyed<D: Mac + hmac::digest::KeyInit>(secret: &[u8]) -> Result<D, OtpError> {
+    <D as Mac>::new_from_slice(secret).map_err(|_| OtpError::InvalidKey)
+}
+
+/// Hashes a message with a clone of an already-keyed HMAC.
+fn hash_keyed<D: Mac + Clone>(hmac: &D, msg: &[u8]) -> HashOutput {
+    let mut hmac = hmac.clone();
+    hmac.update(msg);
+    HashOutput::new(&hmac.finalize().into_bytes())
+}
+
+/// The HMAC output of any [`MacDigest`], stored in a fixed-size buffer.
+///
+/// The buffer fits the largest (64-byte) output, and dereferences to a
+/// slice of the actual output length.
+pub(crate) struct HashOutput {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl HashOutput {
+    /// Copies a hash into the buffer.
+    ///
+    /// # Panics
+    /// This method panics if the hash is longer than 64 bytes.
+    fn new(hash: &[u8]) -> Self {
+        let mut bytes = [0; 64];
+        bytes[..hash.len()].copy_from_slice(hash);
+        HashOutput {
+            bytes,
+            len: hash.len(),
+        }
+    }
+}
+
+impl Deref for HashOutput {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Zeroes out the hash when the [`HashOutput`] struct is dropped.
+#[cfg(feature = "zeroize")]
+impl Drop for HashOutput {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for HashOutput {}
+
 /// A generic method to HMAC a message using the given type.
 ///
-/// This is mainly a private method made for added convenience and code
-/// readability to reduce the duplicate code with different
-/// underlying digests.
+/// This is used when generating with a caller-supplied HMAC type, while
+/// every [`MacDigest`] is hashed through a [`KeyedHmac`].
 ///
 /// # Panics
 /// The method will panic if the provided secret is invalid and a hash
 /// cannot be generated.
-fn hash_internal<D: Mac>(msg: &[u8], secret: &[u8]) -> Vec<u8> {
-    let mut hmac = <D>::new_from_slice(secret).expect("Failed to initialize HMAC");
+pub(crate) fn hash_internal<D: Mac + hmac::digest::KeyInit>(
+    msg: &[u8],
+    secret: &[u8],
+) -> Output<D> {
+    let mut hmac = new_keyed::<D>(secret);
     hmac.update(msg);
-    hmac.finalize().into_bytes()[..].into()
+    hmac.finalize().into_bytes()
+}
+
+/// Compares two byte slices in constant time.
+///
+/// Only a length mismatch returns early, as the length of an OTP isn't
+/// secret. The bytes themselves are compared with [`constant_time_eq_pairs`].
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    constant_time_eq_pairs(a.iter().copied().zip(b.iter().copied()))
+}
+
+/// Checks every pair of bytes is equal without short-circuiting.
+///
+/// Rather than returning at the first differing pair, the difference of every
+/// pair is accumulated, so the whole iterator is always consumed and the time
+/// taken doesn't depend on how much of the input matched. This is the
+/// comparison used when verifying codes, such as by [`OTPResult::matches`].
+///
+/// [`OTPResult::matches`]: crate::otp_result::OTPResult::matches
+pub fn constant_time_eq_pairs<I: IntoIterator<Item = (u8, u8)>>(pairs: I) -> bool {
+    pairs.into_iter().fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The alphabet a base32 secret is encoded with.
+///
+/// Nearly every provider uses the standard [RFC4648] alphabet, which is the
+/// default. The others are only needed to interoperate with the few systems
+/// that use them.
+///
+/// Requires the `std` feature.
+///
+/// [RFC4648]: https://datatracker.ietf.org/doc/html/rfc4648#section-6
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq)]
+pub enum Base32Alphabet {
+    /// The standard alphabet from [RFC4648], `A-Z` followed by `2-7`.
+    ///
+    /// [RFC4648]: https://datatracker.ietf.org/doc/html/rfc4648#section-6
+    #[default]
+    Rfc4648,
+    /// The "extended hex" alphabet from [RFC4648], `0-9` followed by `A-V`.
+    ///
+    /// [RFC4648]: https://datatracker.ietf.org/doc/html/rfc4648#section-7
+    Rfc4648Hex,
+    /// Douglas Crockford's alphabet, which leaves out `I`, `L`, `O` and `U`.
+    Crockford,
+}
+
+#[cfg(feature = "std")]
+impl Base32Alphabet {
+    /// Maps the alphabet to its unpadded `base32` crate equivalent.
+    fn to_alphabet(self) -> Alphabet {
+        match self {
+            Base32Alphabet::Rfc4648 => Alphabet::Rfc4648 { padding: false },
+            Base32Alphabet::Rfc4648Hex => Alphabet::Rfc4648Hex { padding: false },
+            Base32Alphabet::Crockford => Alphabet::Crockford,
+        }
+    }
 }
 
 /// Decodes a base32 string according to RFC4648.
-pub(crate) fn base32_decode(data: &str) -> Option<Vec<u8>> {
-    base32::decode(Alphabet::RFC4648 { padding: false }, data)
+///
+/// The input is normalized the way authenticator apps treat a pasted
+/// secret: lowercase letters are uppercased, whitespace and hyphens are
+/// removed, and any trailing `=` padding is ignored. Returns `None` if the
+/// string contains any other non-base32 character.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    base32_decode_with_alphabet(data, Base32Alphabet::Rfc4648)
+}
+
+/// Decodes a base32 string encoded with the given alphabet.
+///
+/// The input is normalized just like with [`base32_decode`]. Returns `None`
+/// if the string contains any character outside of the alphabet.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn base32_decode_with_alphabet(data: &str, alphabet: Base32Alphabet) -> Option<Vec<u8>> {
+    let normalized: String = data
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    base32::decode(alphabet.to_alphabet(), normalized.trim_end_matches('='))
+}
+
+/// Checks a user-entered base32 secret is well-formed and long enough,
+/// without constructing a generator.
+///
+/// The secret is normalized and decoded just like with [`base32_decode`].
+/// Returns the decoded length in bytes, or a
+/// [`ParseError::SecretParsingError`] if it isn't valid base32, a
+/// [`ParseError::EmptySecret`] if it's empty, and a
+/// [`ParseError::SecretTooShort`] if it's shorter than
+/// [`MIN_SECRET_BYTES`].
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn validate_base32_secret(s: &str) -> Result<usize, ParseError> {
+    let secret = match base32_decode(s) {
+        Some(x) => x,
+        None => return Err(ParseError::SecretParsingError(String::from(s))),
+    };
+    check_secret_not_empty(&secret)?;
+    check_secret_len(&secret)?;
+    Ok(secret.len())
+}
+
+/// Encodes bytes into a base32 string according to RFC4648, without padding.
+///
+/// This is the format secrets are given in otpauth URIs, and can be read
+/// back with [`base32_decode`]. Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn base32_encode(data: &[u8]) -> String {
+    base32::encode(Base32Alphabet::Rfc4648.to_alphabet(), data)
+}
+
+/// Generates an arbitrary secret for fuzzing, which is never empty.
+#[cfg(feature = "arbitrary")]
+pub(crate) fn arbitrary_secret(u: &mut arbitrary::Unstructured) -> arbitrary::Result<Vec<u8>> {
+    let mut secret: Vec<u8> = u.arbitrary()?;
+    if secret.is_empty() {
+        secret.push(u.arbitrary()?);
+    }
+    Ok(secret)
+}
+
+/// A placeholder printed in place of a secret in Debug output.
+///
+/// Only shows the length of the secret, as `[REDACTED; 20 bytes]`.
+pub(crate) struct RedactedSecret(pub(crate) usize);
+
+impl fmt::Debug for RedactedSecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED; {} bytes]", self.0)
+    }
+}
+
+/// Serde helpers to (de)serialize a secret as a base32 string.
+///
+/// Used on the secret fields of [`HOTP`] and [`TOTP`] so serialized
+/// instances are human-readable and match the secret format of otpauth URIs.
+#[cfg(feature = "serde")]
+pub(crate) mod base32_serde {
+    use super::{base32_decode, base32_encode};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(secret: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base32_encode(secret))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base32_decode(&encoded).ok_or_else(|| D::Error::custom("invalid base32 secret"))
+    }
+}
+
+/// Builds an otpauth URI for the given OTP type, label and query parameters.
+///
+/// The label is percent-encoded into the URI's path, and the parameters are
+/// appended to the query in the order given.
+#[cfg(feature = "std")]
+pub(crate) fn build_otpauth_uri(otp_type: &str, label: &str, params: &[(&str, String)]) -> String {
+    let mut uri = Url::parse(&format!("otpauth://{}", otp_type)).expect("Failed to build URI");
+    uri.set_path(label);
+    {
+        let mut query = uri.query_pairs_mut();
+        for (key, value) in params {
+            query.append_pair(key, value);
+        }
+    }
+    uri.into()
+}
+
+/// Decodes a base64 string into bytes.
+///
+/// Both the standard and URL-safe alphabets are accepted, with or without
+/// padding, and surrounding whitespace is ignored. Returns `None` if the
+/// string isn't valid in either alphabet.
+#[cfg(feature = "base64")]
+pub(crate) fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+    use base64::Engine;
+
+    let data = data.trim().trim_end_matches('=');
+    STANDARD_NO_PAD
+        .decode(data)
+        .or_else(|_| URL_SAFE_NO_PAD.decode(data))
+        .ok()
+}
+
+/// Encodes bytes into a padded base64 string, using the standard alphabet.
+#[cfg(feature = "base64")]
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    STANDARD.encode(data)
+}
+
+/// Encodes a string into a QR code.
+///
+/// # Panics
+/// This method panics if the string is too long to fit in a QR code, which
+/// is around 2900 bytes.
+#[cfg(feature = "qr")]
+fn qr_encode(data: &str) -> qrcode::QrCode {
+    qrcode::QrCode::new(data.as_bytes()).expect("Data is too long for a QR code")
+}
+
+/// Renders a string as a QR code in an SVG document.
+#[cfg(feature = "qr")]
+pub(crate) fn qr_svg(data: &str) -> String {
+    use qrcode::render::svg;
+
+    qr_encode(data)
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .build()
+}
+
+/// Renders a string as a QR code in a grayscale PNG image.
+#[cfg(feature = "qr")]
+pub(crate) fn qr_png(data: &str) -> Vec<u8> {
+    use image::{ImageFormat, Luma};
+    use std::io::Cursor;
+
+    let image = qr_encode(data)
+        .render::<Luma<u8>>()
+        .min_dimensions(200, 200)
+        .build();
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .expect("Failed to encode PNG");
+    png
+}
+
+/// Decodes a hex string into bytes.
+///
+/// Both upper and lowercase digits are accepted. Returns `None` if the string
+/// has an odd length or contains a non-hex character.
+pub(crate) fn hex_decode(data: &str) -> Option<Vec<u8>> {
+    if data.len() % 2 != 0 {
+        return None;
+    }
+    data.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            Some((high << 4 | low) as u8)
+        })
+        .collect()
+}
+
+/// Encodes bytes into a lowercase hex string.
+pub(crate) fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The version of the binary layout written by the `to_bytes` methods.
+pub(crate) const BYTES_VERSION: u8 = 1;
+
+/// Reads the fields of the binary layout written by the `to_bytes` methods.
+///
+/// Every read returns a [`ParseError::InvalidBytes`] if the data is
+/// truncated.
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    /// Starts reading the data, checking its version byte first.
+    pub(crate) fn new(data: &'a [u8]) -> Result<Self, ParseError> {
+        let mut reader = ByteReader { data };
+        if reader.read_u8()? != BYTES_VERSION {
+            return Err(ParseError::InvalidBytes);
+        }
+        Ok(reader)
+    }
+
+    /// Reads the given number of bytes.
+    pub(crate) fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        if len > self.data.len() {
+            return Err(ParseError::InvalidBytes);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// Reads a single byte.
+    pub(crate) fn read_u8(&mut self) -> Result<u8, ParseError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// Reads a big-endian `u64`.
+    pub(crate) fn read_u64(&mut self) -> Result<u64, ParseError> {
+        let bytes = self.read_bytes(8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().expect("Read 8 bytes")))
+    }
+
+    /// Reads a secret written by [`write_secret`].
+    pub(crate) fn read_secret(&mut self) -> Result<&'a [u8], ParseError> {
+        let len = self.read_bytes(4)?;
+        let len = u32::from_be_bytes(len.try_into().expect("Read 4 bytes"));
+        self.read_bytes(len as usize)
+    }
+
+    /// Checks all the data was read, so trailing bytes aren't ignored.
+    pub(crate) fn finish(self) -> Result<(), ParseError> {
+        if !self.data.is_empty() {
+            return Err(ParseError::InvalidBytes);
+        }
+        Ok(())
+    }
+}
+
+/// Appends a secret as its big-endian `u32` length followed by its bytes.
+pub(crate) fn write_secret(buf: &mut Vec<u8>, secret: &[u8]) {
+    let len = u32::try_from(secret.len()).expect("Secret too long to encode");
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(secret);
+}
+
+/// Writes a digit count as a single byte.
+///
+/// Counts that don't fit are written as 255, which isn't a valid digit
+/// count, so they're rejected when read back.
+pub(crate) fn digits_byte(digits: u32) -> u8 {
+    u8::try_from(digits).unwrap_or(u8::MAX)
 }
 
 /// Result of an otpauth URI parsing.
@@ -80,12 +893,147 @@ pub(crate) fn base32_decode(data: &str) -> Option<Vec<u8>> {
 ///
 /// If an [HOTP] instance is returned, a second value is returned
 /// signifying the counter's value.
+///
+/// The issuer and account name from the URI's label and query are kept on
+/// the returned instance, and can be read with [`ParseResult::issuer`] and
+/// [`ParseResult::account`].
+///
+/// With the `serde` feature, the result can be serialized to cache a parsed
+/// URI, keeping the counter of an [HOTP] instance alongside it.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParseResult {
     TOTP(TOTP),
     HOTP(HOTP, u64),
 }
 
+/// Result of an otpauth URI parsing with [`parse_otpauth_uri_full`].
+///
+/// Bundles the parsed [`ParseResult`] with the metadata read from the URI,
+/// which is also kept on the parsed instance.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OtpAuth {
+    /// The parsed TOTP or HOTP instance.
+    pub result: ParseResult,
+    /// The issuer, from the `issuer` parameter or the label's prefix.
+    pub issuer: Option<String>,
+    /// The account name from the label.
+    pub account: Option<String>,
+    /// The query parameters that aren't otherwise recognized.
+    pub extras: BTreeMap<String, String>,
+}
+
+/// Label getters for the [`ParseResult`] enum.
+impl ParseResult {
+    /// Gets the issuer of the parsed account, if present.
+    ///
+    /// This is taken from the `issuer` query parameter, or the prefix of
+    /// the label if the parameter is missing.
+    pub fn issuer(&self) -> Option<&str> {
+        match self {
+            ParseResult::TOTP(totp) => totp.get_issuer(),
+            ParseResult::HOTP(hotp, _) => hotp.get_issuer(),
+        }
+    }
+
+    /// Gets the name of the parsed account, if present.
+    ///
+    /// This is the part of the label following the issuer prefix, or the
+    /// entire label if there's no prefix.
+    pub fn account(&self) -> Option<&str> {
+        match self {
+            ParseResult::TOTP(totp) => totp.get_account(),
+            ParseResult::HOTP(hotp, _) => hotp.get_account(),
+        }
+    }
+
+    /// Gets the query parameters of the URI that weren't recognized, such as
+    /// a provider's `image`.
+    ///
+    /// These are kept by the parsed instance and included again when it's
+    /// converted back into a URI.
+    pub fn extras(&self) -> &BTreeMap<String, String> {
+        match self {
+            ParseResult::TOTP(totp) => totp.get_extras(),
+            ParseResult::HOTP(hotp, _) => hotp.get_extras(),
+        }
+    }
+
+    /// Returns whether a [`TOTP`] instance was parsed.
+    pub fn is_totp(&self) -> bool {
+        matches!(self, ParseResult::TOTP(_))
+    }
+
+    /// Returns whether an [`HOTP`] instance was parsed.
+    pub fn is_hotp(&self) -> bool {
+        matches!(self, ParseResult::HOTP(_, _))
+    }
+
+    /// Gets a reference to the parsed [`TOTP`] instance, if the URI was for
+    /// a TOTP.
+    pub fn as_totp(&self) -> Option<&TOTP> {
+        match self {
+            ParseResult::TOTP(totp) => Some(totp),
+            ParseResult::HOTP(_, _) => None,
+        }
+    }
+
+    /// Gets a reference to the parsed [`HOTP`] instance along with its
+    /// counter, if the URI was for an HOTP.
+    pub fn as_hotp(&self) -> Option<(&HOTP, u64)> {
+        match self {
+            ParseResult::TOTP(_) => None,
+            ParseResult::HOTP(hotp, counter) => Some((hotp, *counter)),
+        }
+    }
+
+    /// Converts the result into the parsed [`TOTP`] instance, if the URI was
+    /// for a TOTP.
+    pub fn into_totp(self) -> Option<TOTP> {
+        match self {
+            ParseResult::TOTP(totp) => Some(totp),
+            ParseResult::HOTP(_, _) => None,
+        }
+    }
+
+    /// Converts the result into the parsed [`HOTP`] instance along with its
+    /// counter, if the URI was for an HOTP.
+    pub fn into_hotp(self) -> Option<(HOTP, u64)> {
+        match self {
+            ParseResult::TOTP(_) => None,
+            ParseResult::HOTP(hotp, counter) => Some((hotp, counter)),
+        }
+    }
+}
+
+/// Parses an otpauth URI, just like [`parse_otpauth_uri`].
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl FromStr for ParseResult {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_otpauth_uri(s)
+    }
+}
+
+/// Parses an otpauth URI, just like [`parse_otpauth_uri`].
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+impl TryFrom<&str> for ParseResult {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        parse_otpauth_uri(value)
+    }
+}
+
 /// Different error types of the optauth URI parsing.
 ///
 /// Represents each error that could occur while parsing the otpauth URI
@@ -93,6 +1041,7 @@ pub enum ParseResult {
 /// [url::ParseError] with more information
 #[derive(Debug)]
 pub enum ParseError {
+    #[cfg(feature = "std")]
     UriParseError(url::ParseError),
     WrongScheme(String),
     MissingOtpType,
@@ -100,31 +1049,188 @@ pub enum ParseError {
     MissingSecret,
     SecretParsingError(String),
     UnknownAlgorithm(String),
-    WrongDigitNumber(String),
+    /// The digit count wasn't valid, along with the offending value and
+    /// why it was rejected.
+    WrongDigitNumber { value: String, reason: ValueError },
     MissingCounter,
     WrongCounter(String),
-    InvalidPeriod(String),
+    /// The period wasn't valid, along with the offending value and why it
+    /// was rejected.
+    InvalidPeriod { value: String, reason: ValueError },
+    IssuerMismatch(String, String),
+    MissingMigrationData,
+    InvalidMigrationData(String),
+    SecretTooShort(usize),
+    EmptySecret,
+    InvalidTimeStart(String),
+    /// The data given to a `from_bytes` method was truncated, had trailing
+    /// bytes or used an unknown version or encoding.
+    InvalidBytes,
+    /// The code encoding can't be represented in the requested format.
+    UnsupportedEncoding(String),
+}
+
+/// The reason a digit count or period was rejected, carried by
+/// [`ParseError::WrongDigitNumber`] and [`ParseError::InvalidPeriod`].
+///
+/// This lets an app show a different message for a value that isn't a
+/// number at all and one that's just out of range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValueError {
+    /// The value isn't a number, such as `abc` or `-6`.
+    NotANumber,
+    /// The value is a number with a fractional part, such as a period of
+    /// `1.5` seconds, where only whole numbers are accepted.
+    Fractional,
+    /// The value is a number, but outside of the accepted range: 1 to 10
+    /// digits, or a period of at least 1 second.
+    OutOfRange,
+    /// The value is valid, but can't be represented in the format being
+    /// read or written, such as a Google Authenticator migration payload.
+    Unsupported,
 }
 
+/// Gets why a URI parameter that didn't parse as a whole number was
+/// rejected, telling fractional values apart from ones that aren't numbers.
+#[cfg(feature = "std")]
+fn number_error(value: &str) -> ValueError {
+    match value.parse::<f64>() {
+        Ok(x) if x.is_finite() && x.fract() != 0.0 => ValueError::Fractional,
+        _ => ValueError::NotANumber,
+    }
+}
+
+/// Different error types of OTP generation.
+///
+/// Returned by the fallible generation methods, such as
+/// [`TOTP::try_get_otp`] and [`HOTP::try_get_otp`], in place of the panics
+/// of their infallible counterparts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OtpError {
+    /// The secret couldn't be used to key the HMAC.
+    InvalidKey,
+    /// The TOTP period is 0, so no time step can be computed.
+    InvalidPeriod,
+}
+
+/// Splits an otpauth URI's path into its issuer prefix and account name.
+///
+/// If the path has a literal colon, it's split on the first one and each
+/// part is percent-decoded, so encoded colons are kept within the issuer
+/// and account name. Otherwise the path is percent-decoded, then split with
+/// [`split_label`], as the separator may itself be encoded.
+#[cfg(feature = "std")]
+fn parse_label(path: &str) -> (Option<String>, Option<String>) {
+    let path = path.trim_start_matches('/');
+    let decode = |x: &str| percent_decode_str(x).decode_utf8_lossy().into_owned();
+    match path.split_once(':') {
+        Some((issuer, account)) => label_parts(Some(&decode(issuer)), &decode(account)),
+        None => split_label(&decode(path)),
+    }
+}
+
+/// Splits a label into its issuer prefix and account name.
+///
+/// The label is split on the first colon. Leading whitespace is removed
+/// from the account name, and any empty part is returned as `None`.
+#[cfg(feature = "std")]
+pub(crate) fn split_label(label: &str) -> (Option<String>, Option<String>) {
+    match label.split_once(':') {
+        Some((issuer, account)) => label_parts(Some(issuer), account),
+        None => label_parts(None, label),
+    }
+}
+
+/// Builds the issuer and account name from the parts of a label, removing
+/// leading whitespace from the account name and returning any empty part as
+/// `None`.
+#[cfg(feature = "std")]
+fn label_parts(issuer: Option<&str>, account: &str) -> (Option<String>, Option<String>) {
+    let non_empty = |x: &str| {
+        if x.is_empty() {
+            None
+        } else {
+            Some(String::from(x))
+        }
+    };
+    (issuer.and_then(non_empty), non_empty(account.trim_start()))
+}
+
+/// Percent-encodes the colons in an issuer or account name, along with the
+/// percent signs, so they aren't read as the separator of a label.
+#[cfg(feature = "std")]
+pub(crate) fn escape_label_part(part: &str) -> String {
+    part.replace('%', "%25").replace(':', "%3A")
+}
+
+/// The query parameters of an otpauth URI that are parsed into typed fields,
+/// rather than kept as extras.
+#[cfg(feature = "std")]
+const KNOWN_URI_PARAMS: &[&str] = &[
+    "secret",
+    "issuer",
+    "algorithm",
+    "digits",
+    "period",
+    "t0",
+    "encoder",
+    "counter",
+];
+
 /// Parses an otpauth URI.
 ///
 /// This is generally the string format of QR codes provided by
 /// authentication services
 ///
+/// The OTP type is always read from the host and the label from the path,
+/// so an empty or missing label (`otpauth://totp?secret=...`) is accepted
+/// and leaves the account name unset, rather than being read as the type. A
+/// URI with the type in its path instead (`otpauth:///totp?...`) returns a
+/// [`ParseError::MissingOtpType`].
+///
+/// The scheme and OTP type are case-insensitive, so URIs such as
+/// `OTPAUTH://TOTP/...` are accepted. A TOTP's start time is read from the
+/// non-standard `t0` parameter some exports include, in seconds since the
+/// Unix epoch, and defaults to 0. The non-standard `encoder=steam`
+/// parameter emitted by [`TOTP::to_uri`] and some authenticator apps sets
+/// [`OtpEncoding::Steam`], while any other encoder returns a
+/// [`ParseError::UnsupportedEncoding`]. The secret is normalized just like
+/// with [`base32_decode`], so lowercase, padded or spaced secrets emitted
+/// by some services are read as well. Query parameters that aren't
+/// recognized are kept, and can be read with [`ParseResult::extras`].
+///
+/// The issuer prefix of the label and the `issuer` parameter must match
+/// when both are present, or a [`ParseError::IssuerMismatch`] is returned.
+/// URIs where they disagree were accepted before the issuer was parsed, and
+/// are now rejected.
+///
 /// This method is safe and shouldn't panic. It will return an error if the
 /// provided uri is invalid.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
 pub fn parse_otpauth_uri(uri: &str) -> Result<ParseResult, ParseError> {
+    parse_otpauth_uri_full(uri).map(|auth| auth.result)
+}
+
+/// Parses an otpauth URI just like [`parse_otpauth_uri`], returning the
+/// issuer, account name and unrecognized parameters alongside the parsed
+/// instance.
+///
+/// This saves matching on the [`ParseResult`] to read them, such as when
+/// displaying the account a code belongs to.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn parse_otpauth_uri_full(uri: &str) -> Result<OtpAuth, ParseError> {
     use ParseError::*;
 
-    let parsed_uri = Url::parse(uri);
-    if parsed_uri.is_err() {
-        return Err(UriParseError(parsed_uri.unwrap_err()));
-    }
-    let parsed_uri = parsed_uri.unwrap();
+    let parsed_uri = match Url::parse(uri) {
+        Ok(x) => x,
+        Err(e) => return Err(UriParseError(e)),
+    };
 
-    if !parsed_uri.scheme().eq("otpauth") {
-        return Err(WrongScheme(String::from(parsed_uri.scheme())));
-    }
+    let otp_type = parse_otp_type(&parsed_uri)?;
 
     let query: HashMap<_, _> = parsed_uri.query_pairs().collect();
 
@@ -135,63 +1241,231 @@ pub fn parse_otpauth_uri(uri: &str) -> Result<ParseResult, ParseError> {
         },
         None => return Err(MissingSecret),
     };
+    check_secret_not_empty(&secret)?;
 
     let digits = match query.get("digits") {
         Some(x) => match x.parse::<u32>() {
             Ok(i) => {
-                if i == 0 {
-                    return Err(WrongDigitNumber(String::from(x.as_ref())));
+                if !DIGITS_RANGE.contains(&i) {
+                    return Err(WrongDigitNumber {
+                        value: String::from(x.as_ref()),
+                        reason: ValueError::OutOfRange,
+                    });
                 } else {
                     i
                 }
             }
-            Err(_) => return Err(WrongDigitNumber(String::from(x.as_ref()))),
+            Err(_) => {
+                return Err(WrongDigitNumber {
+                    value: String::from(x.as_ref()),
+                    reason: number_error(x),
+                })
+            }
         },
-        None => 6,
+        None => crate::hotp::DEFAULT_DIGITS,
     };
 
-    let type_str = match parsed_uri.host_str() {
-        Some(x) => x,
-        None => return Err(MissingOtpType),
+    let (label_issuer, account) = parse_label(parsed_uri.path());
+    let issuer = match (label_issuer, query.get("issuer")) {
+        (Some(x), Some(y)) if x != *y => return Err(IssuerMismatch(x, String::from(y.as_ref()))),
+        (_, Some(y)) => Some(String::from(y.as_ref())),
+        (x, None) => x,
     };
 
-    if type_str.eq("totp") {
-        let algo = match query.get("algorithm") {
-            Some(x) => match x.as_ref() {
-                "SHA1" => MacDigest::SHA1,
-                "SHA256" => MacDigest::SHA256,
-                "SHA512" => MacDigest::SHA512,
-                _ => return Err(UnknownAlgorithm(String::from(x.as_ref()))),
-            },
-            None => MacDigest::SHA1,
-        };
-
-        let period = match query.get("period") {
-            Some(x) => match x.parse::<u64>() {
-                Ok(i) => {
-                    if i == 0 {
-                        return Err(InvalidPeriod(String::from(x.as_ref())));
-                    } else {
-                        i
+    let extras: BTreeMap<_, _> = parsed_uri
+        .query_pairs()
+        .filter(|(key, _)| !KNOWN_URI_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    let result = match otp_type {
+        OtpType::Totp => {
+            let algo = match query.get("algorithm") {
+                Some(x) => x.parse::<MacDigest>()?,
+                None => MacDigest::SHA1,
+            };
+
+            let period = match query.get("period") {
+                Some(x) => match x.parse::<u64>() {
+                    Ok(i) => {
+                        if i == 0 {
+                            return Err(InvalidPeriod {
+                                value: String::from(x.as_ref()),
+                                reason: ValueError::OutOfRange,
+                            });
+                        } else {
+                            i
+                        }
                     }
-                }
-                Err(_) => return Err(InvalidPeriod(String::from(x.as_ref()))),
-            },
-            None => 30,
-        };
-
-        Ok(ParseResult::TOTP(TOTP::new(&secret, algo, digits, period)))
-    } else if type_str.eq("hotp") {
-        let counter = match query.get("counter") {
-            Some(x) => match x.parse::<u64>() {
-                Ok(x) => x,
-                Err(_) => return Err(WrongCounter(String::from(x.as_ref()))),
-            },
-            None => return Err(MissingCounter),
-        };
-
-        Ok(ParseResult::HOTP(HOTP::new(&secret, digits), counter))
-    } else {
-        Err(UnknownOtpType(String::from(type_str)))
+                    Err(_) => {
+                        return Err(InvalidPeriod {
+                            value: String::from(x.as_ref()),
+                            reason: number_error(x),
+                        })
+                    }
+                },
+                None => crate::totp::DEFAULT_PERIOD,
+            };
+
+            let time_start = match query.get("t0") {
+                Some(x) => match x.parse::<u64>() {
+                    Ok(x) => x,
+                    Err(_) => return Err(InvalidTimeStart(String::from(x.as_ref()))),
+                },
+                None => 0,
+            };
+
+            let encoding = match query.get("encoder") {
+                Some(x) if x.eq_ignore_ascii_case("steam") => OtpEncoding::Steam,
+                Some(x) => return Err(UnsupportedEncoding(String::from(x.as_ref()))),
+                None => OtpEncoding::Decimal,
+            };
+
+            let totp = TOTP::from_secret_vec(secret, algo, digits, period)
+                .with_time_start(time_start)
+                .with_encoding(encoding)
+                .with_label(issuer.clone(), account.clone())
+                .with_extras(extras.clone());
+            ParseResult::TOTP(totp)
+        }
+        OtpType::Hotp => {
+            let counter = match query.get("counter") {
+                Some(x) => match x.parse::<u64>() {
+                    Ok(x) => x,
+                    Err(_) => return Err(WrongCounter(String::from(x.as_ref()))),
+                },
+                None => return Err(MissingCounter),
+            };
+
+            let hotp = HOTP::from_secret_vec(secret, digits)
+                .with_label(issuer.clone(), account.clone())
+                .with_extras(extras.clone());
+            ParseResult::HOTP(hotp, counter)
+        }
+    };
+
+    Ok(OtpAuth {
+        result,
+        issuer,
+        account,
+        extras,
+    })
+}
+
+/// Generates the code for each pair of a TOTP instance and a time in
+/// seconds, in the order given.
+///
+/// Each result is the same as the one given by [`TOTP::get_otp`]. When
+/// consecutive pairs share the same instance, its HMAC is keyed once and
+/// reused through a [`TotpSession`], so sorting the pairs by instance
+/// speeds up bulk generation.
+///
+/// # Panics
+/// This method panics if any hash's secret is incorrectly given.
+pub fn generate_batch(items: &[(&TOTP, u64)]) -> Vec<OTPResult> {
+    let mut results = Vec::with_capacity(items.len());
+    let mut current: Option<(&TOTP, TotpSession)> = None;
+    for &(totp, time) in items {
+        if current.as_ref().is_some_and(|(last, _)| !core::ptr::eq(*last, totp)) {
+            current = None;
+        }
+        let (_, session) = current.get_or_insert_with(|| (totp, totp.session()));
+        results.push(session.get_otp(time));
+    }
+    results
+}
+
+/// Generates the TOTP code for a borrowed secret at the given time in
+/// seconds, without creating a [`TOTP`] instance.
+///
+/// The result is the same as the one given by [`TOTP::get_otp`] for an
+/// instance with the same parameters, but the secret isn't copied, so no
+/// allocation is made for it. This suits stateless callers generating a
+/// single code; keep a [`TOTP`] around for repeated use.
+///
+/// Time steps are always counted from the Unix epoch, as for an instance
+/// with the default start time of 0. For a custom start time (T0), pass
+/// `time.saturating_sub(time_start)` as the time instead.
+///
+/// # Panics
+/// This method panics if the period is 0, since no time step can be
+/// computed, or if the hash's secret is incorrectly given. Check a period
+/// read from untrusted input before passing it.
+pub fn totp_code(
+    secret: &[u8],
+    mac_digest: MacDigest,
+    digits: u32,
+    period: u64,
+    time: u64,
+) -> OTPResult {
+    let step = time / period;
+    hotp_code_with_digest(secret, &mac_digest, digits, step)
+        .with_seconds_remaining(period - time % period)
+}
+
+/// Generates the HOTP code for a borrowed secret and counter, without
+/// creating an [`HOTP`] instance.
+///
+/// The result is the same as the one given by [`HOTP::get_otp`] for an
+/// instance with the same parameters, but the secret isn't copied, so no
+/// allocation is made for it.
+///
+/// # Panics
+/// This method panics if the hash's secret is incorrectly given.
+pub fn hotp_code(secret: &[u8], digits: u32, counter: u64) -> OTPResult {
+    hotp_code_with_digest(secret, &MacDigest::SHA1, digits, counter)
+}
+
+/// Generates the decimal code for a borrowed secret and counter with the
+/// given digest.
+fn hotp_code_with_digest(
+    secret: &[u8],
+    mac_digest: &MacDigest,
+    digits: u32,
+    counter: u64,
+) -> OTPResult {
+    let hash = hash_generic(&counter.to_be_bytes(), secret, mac_digest);
+    dynamic_truncate(&hash, digits).with_counter(counter)
+}
+
+/// The type of an otpauth URI, as returned by [`otp_type`].
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum OtpType {
+    Hotp,
+    Totp,
+}
+
+/// Reads the type of an otpauth URI, without fully parsing it.
+///
+/// Only the scheme and OTP type are checked, just like the first steps of
+/// [`parse_otpauth_uri`], so the secret isn't decoded and an invalid secret
+/// or parameter isn't reported. This is cheaper when only routing URIs by
+/// type.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn otp_type(uri: &str) -> Result<OtpType, ParseError> {
+    match Url::parse(uri) {
+        Ok(x) => parse_otp_type(&x),
+        Err(e) => Err(ParseError::UriParseError(e)),
+    }
+}
+
+/// Checks the scheme of a parsed otpauth URI and reads its OTP type from
+/// the host.
+#[cfg(feature = "std")]
+fn parse_otp_type(uri: &Url) -> Result<OtpType, ParseError> {
+    use ParseError::*;
+
+    if !uri.scheme().eq_ignore_ascii_case("otpauth") {
+        return Err(WrongScheme(String::from(uri.scheme())));
+    }
+
+    // The host of a non-special URI keeps its case, unlike the scheme
+    match uri.host_str().map(|x| x.to_ascii_lowercase()) {
+        Some(x) if x == "totp" => Ok(OtpType::Totp),
+        Some(x) if x == "hotp" => Ok(OtpType::Hotp),
+        Some(x) => Err(UnknownOtpType(x)),
+        None => Err(MissingOtpType),
     }
 }
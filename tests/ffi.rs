@@ -0,0 +1,202 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::CString;
+use std::os::raw::c_int;
+use std::ptr;
+use xotp::ffi::*;
+#[cfg(feature = "sha3")]
+use xotp::totp::TOTP;
+#[cfg(feature = "sha3")]
+use xotp::util::MacDigest;
+
+static SECRET_BYTES: &[u8] = b"12345678901234567890";
+
+#[test]
+fn test_ffi_totp() {
+    let mut error: c_int = -1;
+    unsafe {
+        let totp = xotp_totp_new(
+            SECRET_BYTES.as_ptr(),
+            SECRET_BYTES.len(),
+            XOTP_DIGEST_SHA1,
+            8,
+            30,
+            &mut error,
+        );
+        assert_eq!(error, XOTP_OK);
+        assert!(!totp.is_null());
+        assert_eq!(xotp_totp_get_otp(totp, 59, &mut error), 94287082);
+        assert_eq!(error, XOTP_OK);
+        xotp_totp_free(totp);
+    }
+}
+
+#[test]
+fn test_ffi_totp_errors() {
+    let mut error: c_int = -1;
+    unsafe {
+        let totp = xotp_totp_new(SECRET_BYTES.as_ptr(), 20, 42, 8, 30, &mut error);
+        assert!(totp.is_null());
+        assert_eq!(error, XOTP_ERR_INVALID_ALGORITHM);
+
+        let totp = xotp_totp_new(
+            SECRET_BYTES.as_ptr(),
+            20,
+            XOTP_DIGEST_SHA384,
+            8,
+            30,
+            &mut error,
+        );
+        assert_eq!(error, XOTP_OK);
+        xotp_totp_free(totp);
+
+        let totp = xotp_totp_new(
+            SECRET_BYTES.as_ptr(),
+            20,
+            XOTP_DIGEST_SHA1,
+            11,
+            30,
+            &mut error,
+        );
+        assert!(totp.is_null());
+        assert_eq!(error, XOTP_ERR_INVALID_DIGITS);
+
+        let totp = xotp_totp_new(ptr::null(), 20, XOTP_DIGEST_SHA1, 6, 30, &mut error);
+        assert!(totp.is_null());
+        assert_eq!(error, XOTP_ERR_NULL_POINTER);
+
+        assert_eq!(xotp_totp_get_otp(ptr::null(), 59, &mut error), 0);
+        assert_eq!(error, XOTP_ERR_NULL_POINTER);
+    }
+}
+
+#[test]
+fn test_ffi_hotp() {
+    let mut error: c_int = -1;
+    unsafe {
+        let hotp = xotp_hotp_new(SECRET_BYTES.as_ptr(), SECRET_BYTES.len(), 6, &mut error);
+        assert_eq!(error, XOTP_OK);
+        assert_eq!(xotp_hotp_get_otp(hotp, 0, ptr::null_mut()), 755224);
+        xotp_hotp_free(hotp);
+    }
+}
+
+#[test]
+fn test_ffi_parse_uri() {
+    let uri = CString::new(
+        "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=1",
+    )
+    .unwrap();
+    let mut error: c_int = -1;
+    let mut result = XotpParseResult {
+        kind: -1,
+        totp: ptr::null_mut(),
+        hotp: ptr::null_mut(),
+        counter: 0,
+    };
+    unsafe {
+        xotp_parse_uri(uri.as_ptr(), &mut result, &mut error);
+        assert_eq!(error, XOTP_OK);
+        assert_eq!(result.kind, XOTP_KIND_HOTP);
+        assert!(result.totp.is_null());
+        assert_eq!(result.counter, 1);
+        assert_eq!(xotp_hotp_get_otp(result.hotp, 1, &mut error), 287082);
+        xotp_hotp_free(result.hotp);
+    }
+}
+
+#[test]
+fn test_ffi_parse_uri_error() {
+    let uri = CString::new("otpauth://totp/ACME?digits=6").unwrap();
+    let mut error: c_int = -1;
+    let mut result = XotpParseResult {
+        kind: -1,
+        totp: ptr::null_mut(),
+        hotp: ptr::null_mut(),
+        counter: 0,
+    };
+    unsafe {
+        xotp_parse_uri(uri.as_ptr(), &mut result, &mut error);
+    }
+    assert_eq!(error, XOTP_ERR_INVALID_SECRET);
+    assert_eq!(result.kind, -1);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_ffi_totp_sha3() {
+    let mut error: c_int = -1;
+    for (digest, mac_digest) in [
+        (XOTP_DIGEST_SHA3_256, MacDigest::SHA3_256),
+        (XOTP_DIGEST_SHA3_512, MacDigest::SHA3_512),
+    ] {
+        let expected = TOTP::new(SECRET_BYTES, mac_digest, 8, 30).get_otp(59);
+        unsafe {
+            let totp = xotp_totp_new(
+                SECRET_BYTES.as_ptr(),
+                SECRET_BYTES.len(),
+                digest,
+                8,
+                30,
+                &mut error,
+            );
+            assert_eq!(error, XOTP_OK);
+            assert_eq!(xotp_totp_get_otp(totp, 59, &mut error), expected.as_u32());
+            xotp_totp_free(totp);
+        }
+    }
+}
+
+// Tests to check the bundled C header matches the exported interface.
+static HEADER: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/include/xotp.h"));
+
+#[test]
+fn test_ffi_header_constants() {
+    let constants = [
+        ("XOTP_OK", XOTP_OK),
+        ("XOTP_ERR_NULL_POINTER", XOTP_ERR_NULL_POINTER),
+        ("XOTP_ERR_INVALID_UTF8", XOTP_ERR_INVALID_UTF8),
+        ("XOTP_ERR_INVALID_URI", XOTP_ERR_INVALID_URI),
+        ("XOTP_ERR_INVALID_SECRET", XOTP_ERR_INVALID_SECRET),
+        ("XOTP_ERR_INVALID_ALGORITHM", XOTP_ERR_INVALID_ALGORITHM),
+        ("XOTP_ERR_INVALID_DIGITS", XOTP_ERR_INVALID_DIGITS),
+        ("XOTP_ERR_INVALID_PERIOD", XOTP_ERR_INVALID_PERIOD),
+        ("XOTP_ERR_INVALID_COUNTER", XOTP_ERR_INVALID_COUNTER),
+        ("XOTP_ERR_PANIC", XOTP_ERR_PANIC),
+        ("XOTP_KIND_TOTP", XOTP_KIND_TOTP),
+        ("XOTP_KIND_HOTP", XOTP_KIND_HOTP),
+        ("XOTP_DIGEST_SHA1", XOTP_DIGEST_SHA1),
+        ("XOTP_DIGEST_SHA256", XOTP_DIGEST_SHA256),
+        ("XOTP_DIGEST_SHA512", XOTP_DIGEST_SHA512),
+        ("XOTP_DIGEST_SHA224", XOTP_DIGEST_SHA224),
+        ("XOTP_DIGEST_SHA384", XOTP_DIGEST_SHA384),
+        ("XOTP_DIGEST_SHA3_256", XOTP_DIGEST_SHA3_256),
+        ("XOTP_DIGEST_SHA3_512", XOTP_DIGEST_SHA3_512),
+    ];
+    let defines = HEADER
+        .lines()
+        .filter_map(|line| line.strip_prefix("#define XOTP_"))
+        .filter(|define| define.contains(' '))
+        .count();
+    assert_eq!(defines, constants.len());
+    for (name, value) in constants {
+        let define = format!("#define {} {}", name, value);
+        assert!(HEADER.lines().any(|line| line == define), "{}", define);
+    }
+}
+
+#[test]
+fn test_ffi_header_functions() {
+    let functions = [
+        "XotpTotp *xotp_totp_new(",
+        "uint32_t xotp_totp_get_otp(",
+        "void xotp_totp_free(",
+        "XotpHotp *xotp_hotp_new(",
+        "uint32_t xotp_hotp_get_otp(",
+        "void xotp_hotp_free(",
+        "void xotp_parse_uri(",
+    ];
+    for function in functions {
+        assert!(HEADER.contains(function), "{}", function);
+    }
+}
@@ -1,8 +1,13 @@
-use xotp::hotp::HOTP;
+use hmac::Hmac;
+use sha1::Sha1;
+use xotp::hotp::{HotpConfig, HotpCounter, DEFAULT_DIGITS, HOTP};
+use xotp::otp_result::OTPResult;
+use xotp::util::{dynamic_truncate, hash_with_name, Base32Alphabet, ParseError, MIN_SECRET_BYTES};
 
 static SECRET_UTF8: &str = "12345678901234567890";
 static SECRET_BYTES: &[u8] = SECRET_UTF8.as_bytes();
 static SECRET_BASE32: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+static SECRET_HEX: &str = "3132333435363738393031323334353637383930";
 
 /// Generic test method to get the HOTP code with
 /// the Secret Key as a byte array
@@ -79,3 +84,525 @@ fn rfc_test_case_9() {
 fn rfc_test_case_10() {
     assert_eq!(run_rfc_test_base32(9), 520489)
 }
+
+// Tests to check the verify method.
+#[test]
+fn test_verify_exact() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify(755224, 0, 0), Some(1));
+}
+
+#[test]
+fn test_verify_within_window() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify(969429, 0, 5), Some(4));
+}
+
+#[test]
+fn test_verify_outside_window() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify(520489, 0, 5), None);
+    assert_eq!(hotp.verify(755224, 1, 5), None);
+}
+
+#[test]
+fn test_verify_same_length_mismatch() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify(155224, 0, 0), None);
+    assert_eq!(hotp.verify(755225, 0, 0), None);
+}
+
+#[test]
+fn test_verify_at_max_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let at_max = hotp.get_otp(u64::MAX).as_u32();
+    assert_eq!(hotp.verify(at_max, u64::MAX, 0), None);
+    assert_eq!(hotp.verify(at_max, u64::MAX, 5), None);
+}
+
+#[test]
+fn test_verify_with_window() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify_with_window(755224, 0, 0), Some((1, 0)));
+    assert_eq!(hotp.verify_with_window(969429, 0, 5), Some((4, 3)));
+    assert_eq!(hotp.verify_with_window(969429, 2, 5), Some((4, 1)));
+    assert_eq!(hotp.verify_with_window(520489, 0, 5), None);
+}
+
+#[test]
+fn test_verify_with_window_at_max_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let before_max = hotp.get_otp(u64::MAX - 1).as_u32();
+    assert_eq!(
+        hotp.verify_with_window(before_max, u64::MAX - 1, 5),
+        Some((u64::MAX, 0))
+    );
+
+    let at_max = hotp.get_otp(u64::MAX).as_u32();
+    assert_eq!(hotp.verify_with_window(at_max, u64::MAX - 1, 5), None);
+    assert_eq!(hotp.verify_with_window(at_max, u64::MAX, 0), None);
+}
+
+#[test]
+fn test_verify_str() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify_str("969429", 0, 5), Some(4));
+    assert_eq!(hotp.verify_str(" 755224 ", 0, 0), Some(1));
+    assert_eq!(hotp.verify_str("75522", 0, 0), None);
+    assert_eq!(hotp.verify_str("abcdef", 0, 0), None);
+}
+
+#[test]
+fn test_verify_str_leading_zeroes() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.verify_str("026920", 30, 0), Some(31));
+    assert_eq!(hotp.verify_str("26920", 30, 0), None);
+}
+
+#[test]
+fn test_verify_str_at_max_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let at_max = hotp.get_otp(u64::MAX).as_string();
+    assert_eq!(hotp.verify_str(&at_max, u64::MAX, 0), None);
+    assert_eq!(hotp.verify_str(&at_max, u64::MAX - 1, 5), None);
+}
+
+#[test]
+fn test_otp_no_seconds_remaining() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.get_otp(0).seconds_remaining(), None);
+}
+
+// Tests to check the fallible base32 constructors.
+#[test]
+fn test_try_from_base32() {
+    let hotp = HOTP::try_default_from_base32(SECRET_BASE32).unwrap();
+    assert_eq!(hotp.get_otp(0).as_u32(), 755224);
+}
+
+#[test]
+fn test_try_from_base32_invalid() {
+    let res = HOTP::try_from_base32("not base32!", 6);
+    assert!(matches!(
+        res.unwrap_err(),
+        ParseError::SecretParsingError { .. }
+    ));
+}
+
+// Tests to check the hex constructors.
+#[test]
+fn test_from_hex() {
+    let hotp = HOTP::default_from_hex(SECRET_HEX).unwrap();
+    assert_eq!(hotp.get_otp(0).as_u32(), 755224);
+    assert_eq!(hotp.get_secret_hex(), SECRET_HEX);
+}
+
+#[test]
+fn test_from_hex_uppercase() {
+    let hotp = HOTP::new_from_hex(&SECRET_HEX.to_uppercase(), 6).unwrap();
+    assert_eq!(hotp.get_otp(1).as_u32(), 287082);
+}
+
+#[test]
+fn test_from_hex_invalid() {
+    assert!(matches!(
+        HOTP::default_from_hex("313").unwrap_err(),
+        ParseError::SecretParsingError { .. }
+    ));
+    assert!(matches!(
+        HOTP::default_from_hex("31zz").unwrap_err(),
+        ParseError::SecretParsingError { .. }
+    ));
+}
+
+// Tests to check the secret getters.
+#[test]
+fn test_get_secret() {
+    let hotp = HOTP::default_from_base32(SECRET_BASE32);
+    assert_eq!(hotp.get_secret(), SECRET_BYTES);
+    assert_eq!(hotp.get_secret_base32(), SECRET_BASE32);
+}
+
+// Tests to check digit count validation.
+#[test]
+fn test_zero_digits_unvalidated() {
+    let hotp = HOTP::new(SECRET_BYTES, 0);
+    assert_eq!(hotp.get_otp(0).as_u32(), 0);
+    assert_eq!(hotp.get_otp(1).as_u32(), 0);
+}
+
+// Tests to check 7 and 8-digit codes against the RFC's truncated values.
+#[test]
+fn test_seven_digits() {
+    let hotp = HOTP::new(SECRET_BYTES, 7);
+    assert_eq!(hotp.get_otp(0).as_u32(), 4755224);
+    assert_eq!(hotp.get_otp(2).as_string(), "7359152");
+    assert_eq!(hotp.get_otp(7).as_string(), "2162583");
+    assert_eq!(hotp.verify_str("7359152", 2, 0), Some(3));
+}
+
+#[test]
+fn test_eight_digits() {
+    let hotp = HOTP::new(SECRET_BYTES, 8);
+    assert_eq!(hotp.get_otp(0).as_u32(), 84755224);
+    assert_eq!(hotp.get_otp(2).as_string(), "37359152");
+    assert_eq!(hotp.get_otp(7).as_string(), "82162583");
+    assert_eq!(hotp.get_otp(9).as_string(), "45520489");
+}
+
+#[test]
+fn test_try_new() {
+    assert!(HOTP::try_new(SECRET_BYTES, 6).is_ok());
+    assert!(HOTP::try_new(SECRET_BYTES, 10).is_ok());
+}
+
+#[test]
+fn test_try_new_invalid_digits() {
+    assert!(matches!(
+        HOTP::try_new(SECRET_BYTES, 0).unwrap_err(),
+        ParseError::WrongDigitNumber { .. }
+    ));
+    assert!(matches!(
+        HOTP::try_new(SECRET_BYTES, 11).unwrap_err(),
+        ParseError::WrongDigitNumber { .. }
+    ));
+}
+
+// Tests to check generation with a caller-supplied HMAC type.
+#[test]
+fn test_get_otp_generic() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.get_otp_generic::<Hmac<Sha1>>(7).as_u32(), 162583);
+}
+
+// Tests to check serde support.
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let hotp = HOTP::default_from_base32(SECRET_BASE32);
+    let json = serde_json::to_string(&hotp).unwrap();
+    assert!(json.contains(SECRET_BASE32));
+
+    let deserialized: HOTP = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.get_otp(0).as_u32(), 755224);
+}
+
+// Tests to check the secret isn't exposed in the Debug output.
+#[test]
+fn test_debug_redacts_secret() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let debug = format!("{:?}", hotp);
+    assert!(debug.contains("digits: 6"));
+    assert!(debug.contains("secret: [REDACTED; 20 bytes]"));
+    assert!(!debug.contains("49, 50, 51"));
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_zeroize_clone() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let cloned = hotp.clone();
+    drop(hotp);
+    assert_eq!(cloned.get_otp(0).as_u32(), 755224);
+}
+
+// Tests to check iterating over codes for a counter range.
+#[test]
+fn test_codes_for_counters() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let codes: Vec<_> = hotp.codes_for_counters(0..3).collect();
+    assert_eq!(codes.len(), 3);
+    assert_eq!(codes[0].0, 0);
+    assert_eq!(codes[0].1.as_u32(), 755224);
+    assert_eq!(codes[2].0, 2);
+    assert_eq!(codes[2].1.as_u32(), 359152);
+}
+
+#[test]
+fn test_codes_for_counters_empty() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.codes_for_counters(5..5).count(), 0);
+}
+
+// Tests to check HOTP instances can be compared.
+#[test]
+fn test_hotp_eq() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp, HOTP::default_from_base32(SECRET_BASE32));
+    assert_ne!(hotp, HOTP::new(SECRET_BYTES, 8));
+    assert_ne!(hotp, HOTP::new(b"another secret", 6));
+}
+
+#[test]
+fn test_hotp_hash_set() {
+    let mut set = std::collections::HashSet::new();
+    set.insert(HOTP::new(SECRET_BYTES, 6));
+    set.insert(HOTP::default_from_base32(SECRET_BASE32));
+    set.insert(HOTP::new(SECRET_BYTES, 8));
+    assert_eq!(set.len(), 2);
+}
+
+// Tests to check results carry the counter they were generated from.
+#[test]
+fn test_result_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.get_otp(0).counter(), 0);
+    assert_eq!(hotp.get_otp(42).counter(), 42);
+    assert_eq!(hotp.get_otp(u64::MAX).counter(), u64::MAX);
+}
+
+#[test]
+fn test_result_equality_ignores_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.get_otp(1), OTPResult::new(6, 287082));
+}
+
+// Tests to check the default digit count matches the default constructors.
+#[test]
+fn test_default_digits() {
+    assert_eq!(
+        HOTP::default_from_utf8(SECRET_UTF8).get_digits(),
+        DEFAULT_DIGITS
+    );
+}
+
+// Tests to check owned secrets can be moved into an instance.
+#[test]
+fn test_from_secret_vec() {
+    let hotp = HOTP::from_secret_vec(SECRET_BYTES.to_vec(), 6);
+    assert_eq!(hotp, HOTP::new(SECRET_BYTES, 6));
+    assert_eq!(hotp.get_otp(0).as_u32(), 755224);
+}
+
+// Tests to check the stateful counter-tracking generator.
+#[test]
+fn test_hotp_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let mut generator = HotpCounter::new(hotp.clone(), 0);
+    for counter in 0..3 {
+        assert_eq!(generator.counter(), counter);
+        assert_eq!(generator.next_otp(), hotp.get_otp(counter));
+    }
+    assert_eq!(generator.counter(), 3);
+    assert_eq!(generator.hotp(), &hotp);
+}
+
+#[test]
+fn test_hotp_counter_resume() {
+    let mut generator = HotpCounter::new(HOTP::new(SECRET_BYTES, 6), 9);
+    assert_eq!(generator.next_otp().as_u32(), 520489);
+    assert_eq!(generator.counter(), 10);
+}
+
+// Tests to check the fallible generation matches the infallible one.
+#[test]
+fn test_try_get_otp() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(hotp.try_get_otp(1), Ok(hotp.get_otp(1)));
+    assert_eq!(hotp.try_get_otp(1).unwrap().as_string(), "287082");
+}
+
+// Tests to check secrets can be decoded with the base32hex alphabet.
+#[test]
+fn test_new_from_base32_with_alphabet() {
+    let hotp = HOTP::new_from_base32_with_alphabet(
+        "64P36D1L6ORJGE9G64P36D1L6ORJGE9G",
+        Base32Alphabet::Rfc4648Hex,
+        6,
+    );
+    assert_eq!(hotp, HOTP::new(SECRET_BYTES, 6));
+    assert!(matches!(
+        HOTP::try_from_base32_with_alphabet(SECRET_BASE32, Base32Alphabet::Rfc4648Hex, 6),
+        Err(ParseError::SecretParsingError(_))
+    ));
+}
+
+// Tests to check arbitrary instances stay within the valid parameter ranges.
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    for seed in [
+        &[][..],
+        &[0xff; 64],
+        b"an arbitrary fuzzer input of some length",
+    ] {
+        let hotp = HOTP::arbitrary(&mut Unstructured::new(seed)).unwrap();
+        assert!(HOTP::try_new(hotp.get_secret(), hotp.get_digits()).is_ok());
+    }
+}
+
+// Tests to check the raw HMAC matches the intermediate values in RFC4226.
+#[test]
+fn test_hmac_for_counter() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    assert_eq!(
+        hotp.hmac_for_counter(0),
+        [
+            0xcc, 0x93, 0xcf, 0x18, 0x50, 0x8d, 0x94, 0x93, 0x4c, 0x64, 0xb6, 0x5d, 0x8b, 0xa7,
+            0x66, 0x7f, 0xb7, 0xcd, 0xe4, 0xb0
+        ]
+    );
+    assert_eq!(hotp.hmac_for_counter(1).len(), 20);
+}
+
+// Tests to check the truncation offset can be fixed for legacy tokens.
+#[test]
+fn test_get_otp_with_offset() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    // The RFC4226 HMAC for counter 0 ends in 0xb0, so its dynamic offset is 0
+    assert_eq!(hotp.get_otp_with_offset(0, None), hotp.get_otp(0));
+    assert_eq!(hotp.get_otp_with_offset(0, Some(0)).as_u32(), 755224);
+    assert_eq!(hotp.get_otp_with_offset(0, Some(16)).as_u32(), 240304);
+}
+
+// Tests to check the minimum secret length is enforced when requested.
+#[test]
+fn test_try_new_checked() {
+    assert!(HOTP::try_new_checked(SECRET_BYTES, 6).is_ok());
+    assert!(HOTP::try_new_checked(&SECRET_BYTES[..MIN_SECRET_BYTES], 6).is_ok());
+    assert!(matches!(
+        HOTP::try_new_checked(&SECRET_BYTES[..MIN_SECRET_BYTES - 1], 6),
+        Err(ParseError::SecretTooShort(15))
+    ));
+}
+
+// Tests to check an empty secret is rejected by the fallible constructors.
+#[test]
+fn test_try_new_empty_secret() {
+    assert!(matches!(
+        HOTP::try_new(b"", 6),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::try_new_checked(b"", 6),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+#[test]
+fn test_decoding_constructors_empty_secret() {
+    assert!(matches!(
+        HOTP::try_from_base32("", 6),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::try_from_base32_with_alphabet("", Base32Alphabet::Crockford, 6),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::try_default_from_base32(""),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::new_from_hex("", 6),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::default_from_hex(""),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn test_base64_empty_secret() {
+    assert!(matches!(
+        HOTP::new_from_base64("", 6),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        HOTP::default_from_base64(""),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+// Tests to check the issuer and account metadata.
+#[test]
+fn test_default_label() {
+    let hotp = HOTP::default_from_utf8(SECRET_UTF8);
+    assert_eq!(hotp.get_issuer(), None);
+    assert_eq!(hotp.get_account(), None);
+    assert!(hotp.get_extras().is_empty());
+}
+
+#[test]
+fn test_with_label() {
+    let hotp = HOTP::default_from_utf8(SECRET_UTF8)
+        .with_issuer("ACME Co")
+        .with_account("john.doe@email.com");
+    assert_eq!(hotp.get_issuer(), Some("ACME Co"));
+    assert_eq!(hotp.get_account(), Some("john.doe@email.com"));
+}
+
+// Tests to check the Display summary doesn't expose the secret.
+#[test]
+fn test_display() {
+    assert_eq!(HOTP::new(SECRET_BYTES, 6).to_string(), "HOTP(6 digits)");
+}
+
+// Tests to check the base64 constructors.
+#[cfg(feature = "base64")]
+#[test]
+fn test_new_from_base64() {
+    let secret = [0xfb, 0xff, 0xbf, 0x00, 0x01];
+    let hotp = HOTP::new(&secret, 6);
+    assert_eq!(hotp.get_secret_base64(), "+/+/AAE=");
+
+    for encoded in ["+/+/AAE=", "+/+/AAE", "-_-_AAE=", "-_-_AAE"] {
+        assert_eq!(HOTP::default_from_base64(encoded).unwrap(), hotp);
+    }
+    assert!(matches!(
+        HOTP::new_from_base64("+/+/A", 6),
+        Err(ParseError::SecretParsingError(_))
+    ));
+}
+
+// Tests to check instances built from a config.
+#[test]
+fn test_from_config() {
+    assert_eq!(HotpConfig::default().digits, DEFAULT_DIGITS);
+    assert_eq!(
+        HOTP::from_config(SECRET_BYTES, &HotpConfig::default()),
+        HOTP::default_from_secret(SECRET_BYTES)
+    );
+
+    let hotp = HOTP::from_config(SECRET_BYTES, &HotpConfig { digits: 8 });
+    assert_eq!(hotp.get_otp(0).as_u32(), 84755224);
+}
+
+// Tests to check codes for counters of a non-standard width.
+#[test]
+fn test_get_otp_with_counter_bytes() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    for counter in [0_u64, 1, 9] {
+        let code = hotp.get_otp_with_counter_bytes(&counter.to_be_bytes());
+        assert_eq!(code.as_u32(), hotp.get_otp(counter).as_u32());
+    }
+
+    let hash = hash_with_name(&1_u32.to_be_bytes(), SECRET_BYTES, "SHA1").unwrap();
+    let code = hotp.get_otp_with_counter_bytes(&1_u32.to_be_bytes());
+    assert_eq!(code, dynamic_truncate(&hash, 6));
+    assert_ne!(code.as_u32(), 287082);
+    assert_eq!(code.counter(), 0);
+}
+
+// Tests to check the binary layout round-trips.
+#[test]
+fn test_bytes_round_trip() {
+    let hotp = HOTP::new(SECRET_BYTES, 8);
+    let bytes = hotp.to_bytes();
+    assert_eq!(bytes[..6], [1, 8, 0, 0, 0, 20]);
+    assert_eq!(&bytes[6..], SECRET_BYTES);
+    assert_eq!(HOTP::from_bytes(&bytes).unwrap(), hotp);
+
+    assert!(matches!(
+        HOTP::from_bytes(&bytes[..10]),
+        Err(ParseError::InvalidBytes)
+    ));
+    assert!(matches!(
+        HOTP::from_bytes(&[1, 6, 0, 0, 0, 0]),
+        Err(ParseError::EmptySecret)
+    ));
+}
@@ -12,4 +12,78 @@ fn test_padding_needed() {
 fn test_padding_not_needed() {
     let result = OTPResult::new(6, 123456);
     assert_eq!("123456", result.as_string())
-}
\ No newline at end of file
+}
+
+// Tests whether a result survives a serde round-trip
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let result = OTPResult::new(6, 1234);
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(serde_json::from_str::<OTPResult>(&json).unwrap(), result)
+}
+
+// Tests whether the code is padded to the wider of the width and digit count
+#[test]
+fn test_as_string_padded() {
+    let result = OTPResult::new(6, 1234);
+    assert_eq!(result.as_string_padded(8), "00001234");
+    assert_eq!(result.as_string_padded(6), "001234");
+    assert_eq!(result.as_string_padded(4), "001234");
+    assert_eq!(result.as_string_padded(0), result.as_string());
+}
+
+// Tests whether the digits of a code are zero-padded, most-significant first
+#[test]
+fn test_as_digits() {
+    assert_eq!(OTPResult::new(6, 1234).as_digits(), vec![0, 0, 1, 2, 3, 4]);
+    assert_eq!(
+        OTPResult::new(6, 123456).as_digits(),
+        vec![1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(OTPResult::new(8, 0).as_digits(), vec![0; 8]);
+}
+
+// Tests whether user input is matched against the zero-padded code
+#[test]
+fn test_matches() {
+    let result = OTPResult::new(6, 1234);
+    assert!(result.matches("001234"));
+    assert!(result.matches(" 001234\n"));
+    assert!(!result.matches("1234"));
+    assert!(!result.matches("0001234"));
+    assert!(!result.matches("001235"));
+    assert!(!result.matches(""));
+}
+
+// Tests whether codes are split into groups for display
+#[test]
+fn test_as_grouped_string() {
+    let result = OTPResult::new(6, 1234);
+    assert_eq!(result.as_grouped_string(3, " "), "001 234");
+    assert_eq!(result.as_grouped_string(2, "-"), "00-12-34");
+    assert_eq!(result.as_grouped_string(4, " "), "0012 34");
+    assert_eq!(result.as_grouped_string(6, " "), "001234");
+    assert_eq!(result.as_grouped_string(0, " "), "001234");
+}
+
+#[test]
+fn test_as_grouped() {
+    assert_eq!(OTPResult::new(6, 123456).as_grouped(), "123 456");
+    assert_eq!(OTPResult::new(8, 12345678).as_grouped(), "1234 5678");
+    assert_eq!(OTPResult::new(7, 1234567).as_grouped(), "123 456 7");
+    assert_eq!(OTPResult::new(9, 123456789).as_grouped(), "123 456 789");
+}
+
+// Tests whether a 10-digit code above the u32 range is held and formatted
+#[test]
+fn test_new_u64() {
+    let result = OTPResult::new_u64(10, 9_876_543_210);
+    assert_eq!(result.as_u64(), 9_876_543_210);
+    assert_eq!(result.as_string(), "9876543210");
+    assert_eq!(result.as_u32(), 9_876_543_210_u64 as u32);
+
+    let result = OTPResult::new_u64(10, 1234);
+    assert_eq!(result, OTPResult::new(10, 1234));
+    assert_eq!(result.as_string(), "0000001234");
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use xotp::hotp::HOTP;
+use xotp::throttle::{AttemptStore, MemoryAttemptStore, ThrottleResult, ThrottledVerifier};
+use xotp::totp::{VerifyResult, TOTP};
+use xotp::util::MacDigest;
+
+static SECRET_BYTES: &[u8] = b"12345678901234567890";
+
+// Tests to check TOTP codes are refused after too many failures in a step.
+#[test]
+fn test_throttled_totp() {
+    let totp = TOTP::new(SECRET_BYTES, MacDigest::SHA1, 8, 30);
+    let mut verifier = ThrottledVerifier::new(totp, 2);
+    assert_eq!(
+        verifier.verify(94287082, 59, 0),
+        ThrottleResult::Checked(VerifyResult::Valid)
+    );
+    assert_eq!(
+        verifier.verify(94287082, 89, 1),
+        ThrottleResult::Checked(VerifyResult::ValidWithinSkew(-1))
+    );
+
+    assert_eq!(
+        verifier.verify(12345678, 59, 0),
+        ThrottleResult::Checked(VerifyResult::Invalid)
+    );
+    assert!(!verifier.is_rate_limited(1));
+    assert_eq!(
+        verifier.verify(12345678, 59, 0),
+        ThrottleResult::Checked(VerifyResult::Invalid)
+    );
+    assert!(verifier.is_rate_limited(1));
+
+    // Even the correct code is refused for the rest of the step
+    assert_eq!(
+        verifier.verify(94287082, 59, 0),
+        ThrottleResult::RateLimited
+    );
+    assert!(!ThrottleResult::RateLimited.is_valid());
+    assert!(ThrottleResult::Checked(VerifyResult::Valid).is_valid());
+
+    // The limit resets with the next step
+    assert_eq!(
+        verifier.verify(14050471, 1111111111, 0),
+        ThrottleResult::Checked(VerifyResult::Valid)
+    );
+}
+
+// Tests to check HOTP codes are refused after too many failures for a counter.
+#[test]
+fn test_throttled_hotp() {
+    let mut verifier = ThrottledVerifier::new(HOTP::new(SECRET_BYTES, 6), 3);
+    assert_eq!(
+        verifier.verify(755224, 0, 0),
+        ThrottleResult::Checked(VerifyResult::Valid)
+    );
+    assert_eq!(
+        verifier.verify(359152, 0, 2),
+        ThrottleResult::Checked(VerifyResult::ValidWithinSkew(2))
+    );
+
+    for _ in 0..3 {
+        assert_eq!(
+            verifier.verify(0, 1, 2),
+            ThrottleResult::Checked(VerifyResult::Invalid)
+        );
+    }
+    assert_eq!(verifier.store().failures(1), 3);
+    assert_eq!(verifier.verify(287082, 1, 0), ThrottleResult::RateLimited);
+    assert_eq!(
+        verifier.verify(359152, 2, 0),
+        ThrottleResult::Checked(VerifyResult::Valid)
+    );
+}
+
+// Tests to check failures are kept in an injected store.
+#[derive(Default)]
+struct MapStore(HashMap<u64, u32>);
+
+impl AttemptStore for MapStore {
+    fn failures(&self, factor: u64) -> u32 {
+        self.0.get(&factor).copied().unwrap_or(0)
+    }
+
+    fn record_failure(&mut self, factor: u64) {
+        *self.0.entry(factor).or_insert(0) += 1;
+    }
+}
+
+#[test]
+fn test_throttled_custom_store() {
+    let hotp = HOTP::new(SECRET_BYTES, 6);
+    let mut verifier = ThrottledVerifier::with_store(hotp, MapStore::default(), 1);
+    assert_eq!(
+        verifier.verify(0, 4, 0),
+        ThrottleResult::Checked(VerifyResult::Invalid)
+    );
+    assert_eq!(verifier.verify(338314, 4, 0), ThrottleResult::RateLimited);
+    assert_eq!(verifier.store().0.get(&4), Some(&1));
+    assert_eq!(verifier.max_failures(), 1);
+}
+
+// Tests to check the memory store drops failures of earlier factors.
+#[test]
+fn test_memory_store() {
+    let mut store = MemoryAttemptStore::new();
+    store.record_failure(5);
+    store.record_failure(5);
+    assert_eq!(store.failures(5), 2);
+    store.record_failure(6);
+    assert_eq!(store.failures(5), 0);
+    assert_eq!(store.failures(6), 1);
+}
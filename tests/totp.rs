@@ -1,5 +1,12 @@
-use xotp::totp::TOTP;
-use xotp::util::MacDigest;
+use hmac::Hmac;
+use sha2::Sha256;
+use std::time::{Duration, UNIX_EPOCH};
+use xotp::hotp::HOTP;
+use xotp::otp_result::OTPResult;
+use xotp::totp::{TotpBuilder, TotpConfig, VerifyResult, DEFAULT_DIGITS, DEFAULT_PERIOD, TOTP};
+use xotp::util::{
+    Base32Alphabet, Clock, MacDigest, OtpEncoding, OtpError, ParseError, SystemClock, ValueError,
+};
 
 // RFC6238 SHA1 Secret
 static SECRET_UTF8_SHA1: &str = "12345678901234567890";
@@ -86,6 +93,7 @@ fn rfc_test_1_sha1() {
 }
 
 #[test]
+#[allow(clippy::zero_prefixed_literal)]
 fn rfc_test_2_sha1() {
     assert_eq!(run_rfc_test_bytes(1111111109), 07081804)
 }
@@ -239,4 +247,1144 @@ fn test_time_until_with_start() {
 fn test_time_until_with_start_at_edge() {
     let totp = TOTP::default_from_base32("SecretKey");
     assert_eq!(totp.time_until_refresh_with_start(45, 15), 30)
-}
\ No newline at end of file
+}
+
+// Tests to check the time_remaining methods.
+#[test]
+fn test_time_remaining() {
+    let totp = TOTP::default_from_base32("SecretKey");
+    assert_eq!(totp.time_remaining(10), 20);
+    assert_eq!(totp.time_remaining(59), 1);
+}
+
+#[test]
+fn test_time_remaining_at_edge() {
+    let totp = TOTP::default_from_base32("SecretKey");
+    assert_eq!(totp.time_remaining(60), 30);
+}
+
+#[test]
+fn test_time_remaining_with_start() {
+    let totp = TOTP::default_from_base32("SecretKey");
+    assert_eq!(totp.time_remaining_with_start(40, 15), 5);
+    assert_eq!(totp.time_remaining_with_start(75, 15), 30);
+}
+
+// Tests to check the verify methods.
+#[test]
+fn test_verify() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify(94287082, 59));
+    assert!(!totp.verify(94287083, 59));
+}
+
+#[test]
+fn test_verify_leading_zeroes() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify(7081804, 1111111109));
+}
+
+#[test]
+fn test_verify_str() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify_str("07081804", 1111111109, 0));
+    assert!(totp.verify_str(" 07081804\n", 1111111109, 0));
+    assert!(!totp.verify_str("7081804", 1111111109, 0));
+    assert!(!totp.verify_str("007081804", 1111111109, 0));
+    assert!(!totp.verify_str("abc", 1111111109, 0));
+}
+
+#[test]
+fn test_verify_str_with_skew() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify_str("94287082", 60, 1));
+    assert!(!totp.verify_str("94287082", 60, 0));
+}
+
+#[test]
+fn test_verify_with_skew() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    // The code for 59 belongs to the step before 60 and the one after 29
+    assert!(totp.verify_with_skew(94287082, 60, 1));
+    assert!(totp.verify_with_skew(94287082, 29, 1));
+    assert!(!totp.verify_with_skew(94287082, 90, 1));
+}
+
+#[test]
+fn test_verify_with_zero_skew() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify_with_skew(94287082, 59, 0));
+    assert!(!totp.verify_with_skew(94287082, 60, 0));
+}
+
+#[test]
+fn test_verify_returning_offset() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.verify_returning_offset(94287082, 59, 1), Some(0));
+    assert_eq!(totp.verify_returning_offset(94287082, 60, 1), Some(-1));
+    assert_eq!(totp.verify_returning_offset(94287082, 29, 1), Some(1));
+    assert_eq!(totp.verify_returning_offset(94287082, 90, 1), None);
+}
+
+#[test]
+fn test_verify_returning_offset_near_epoch() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.verify_returning_offset(0, 0, 2), None);
+}
+
+// Codes of the same length differing in the first or last digit both fail
+#[test]
+fn test_verify_same_length_mismatch() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify(94287082, 59));
+    assert!(!totp.verify(14287082, 59));
+    assert!(!totp.verify(94287081, 59));
+}
+
+#[test]
+fn test_verify_longer_code() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 6, 30);
+    assert!(totp.verify(287082, 59));
+    assert!(!totp.verify(94287082, 59));
+}
+
+// Tests to check the remaining validity carried by the result.
+#[test]
+fn test_otp_seconds_remaining() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp(59).seconds_remaining(), Some(1));
+    assert_eq!(totp.get_otp(60).seconds_remaining(), Some(30));
+}
+
+#[test]
+fn test_otp_seconds_remaining_with_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let result = totp.get_otp_with_custom_time_start(50, 15);
+    assert_eq!(result.seconds_remaining(), Some(25));
+}
+
+#[test]
+fn test_otp_equality_ignores_seconds_remaining() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let hash = |result: &OTPResult| {
+        let mut hasher = DefaultHasher::new();
+        result.hash(&mut hasher);
+        hasher.finish()
+    };
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp(59), totp.get_otp(58));
+    assert_eq!(totp.get_otp(59), OTPResult::new(8, 94287082));
+    assert_eq!(hash(&totp.get_otp(59)), hash(&OTPResult::new(8, 94287082)));
+    assert_ne!(totp.get_otp(59), OTPResult::new(6, 94287082));
+}
+
+// Tests to check the issuer and account metadata.
+#[test]
+fn test_default_label() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1);
+    assert_eq!(totp.get_issuer(), None);
+    assert_eq!(totp.get_account(), None);
+}
+
+#[test]
+fn test_with_label() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1)
+        .with_issuer("ACME Co")
+        .with_account("john.doe@email.com");
+    assert_eq!(totp.get_issuer(), Some("ACME Co"));
+    assert_eq!(totp.get_account(), Some("john.doe@email.com"));
+}
+
+// Tests to check Steam Guard code generation.
+#[test]
+fn test_steam_codes() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1).with_encoding(OtpEncoding::Steam);
+    assert_eq!(totp.get_otp(59).as_string(), "PV9M4");
+    assert_eq!(totp.get_otp(1111111109).as_string(), "PY4YB");
+}
+
+#[test]
+fn test_steam_verify_str() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1).with_encoding(OtpEncoding::Steam);
+    assert!(totp.verify_str("PV9M4", 59, 0));
+    assert!(!totp.verify_str("PV9M5", 59, 0));
+}
+
+// Tests to check the fallible base32 constructors.
+#[test]
+fn test_try_from_base32() {
+    let totp = TOTP::try_from_base32(SECRET_BASE32_SHA1, MacDigest::SHA1, 8, 30).unwrap();
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+}
+
+#[test]
+fn test_try_default_from_base32_invalid() {
+    let res = TOTP::try_default_from_base32("not base32!");
+    assert!(matches!(
+        res.unwrap_err(),
+        ParseError::SecretParsingError { .. }
+    ));
+}
+
+// Tests to check the hex constructors.
+#[test]
+fn test_from_hex() {
+    let totp = TOTP::new_from_hex(
+        "3132333435363738393031323334353637383930",
+        MacDigest::SHA1,
+        8,
+        30,
+    )
+    .unwrap();
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+}
+
+#[test]
+fn test_from_hex_invalid() {
+    let res = TOTP::default_from_hex("xyz");
+    assert!(matches!(
+        res.unwrap_err(),
+        ParseError::SecretParsingError { .. }
+    ));
+}
+
+// Tests to check the secret getters.
+#[test]
+fn test_get_secret_round_trip() {
+    let totp = TOTP::new_from_utf8(SECRET_UTF8_SHA256, MacDigest::SHA256, 8, 30);
+    assert_eq!(totp.get_secret(), SECRET_BYTES_SHA256);
+    assert_eq!(totp.get_secret_base32(), SECRET_BASE32_SHA256);
+
+    let rebuilt = TOTP::new_from_base32(&totp.get_secret_base32(), MacDigest::SHA256, 8, 30);
+    assert_eq!(rebuilt.get_otp(59), totp.get_otp(59));
+}
+
+// Tests to check 7 and 8-digit codes keep the low digits of the RFC vectors.
+#[test]
+fn test_seven_digits() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 7, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 4287082);
+    assert_eq!(totp.get_otp(1111111109).as_string(), "7081804");
+    assert_eq!(totp.get_otp(1234567890).as_string(), "9005924");
+    assert_eq!(totp.get_otp(59).as_grouped(), "428 708 2");
+    assert!(totp.verify_str("7081804", 1111111109, 0));
+}
+
+#[test]
+fn test_eight_digits() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 7081804);
+    assert_eq!(totp.get_otp(1111111109).as_string(), "07081804");
+    assert_eq!(totp.get_otp(1111111109).as_grouped(), "0708 1804");
+    assert_eq!(
+        totp.get_otp(2000000000).as_digits(),
+        vec![6, 9, 2, 7, 9, 0, 3, 7]
+    );
+    assert!(totp.verify_str("07081804", 1111111109, 0));
+}
+
+// Tests to check codes longer than 8 digits.
+#[test]
+fn test_nine_digits() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 9, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+    assert_eq!(totp.get_otp(59).as_string(), "094287082");
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 907081804);
+}
+
+#[test]
+fn test_ten_digits() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 10, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 1094287082);
+    assert_eq!(totp.get_otp(20000000000).as_string(), "1465353130");
+}
+
+// Tests to check digit count and period validation.
+#[test]
+fn test_try_new() {
+    let totp = TOTP::try_new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30).unwrap();
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+}
+
+#[test]
+fn test_try_new_invalid_digits() {
+    let res = TOTP::try_new(SECRET_BYTES_SHA1, MacDigest::SHA1, 0, 30);
+    assert!(matches!(
+        res.unwrap_err(),
+        ParseError::WrongDigitNumber { .. }
+    ));
+}
+
+#[test]
+fn test_try_new_invalid_period() {
+    let res = TOTP::try_new(SECRET_BYTES_SHA1, MacDigest::SHA1, 6, 0);
+    assert!(matches!(res.unwrap_err(), ParseError::InvalidPeriod { .. }));
+}
+
+// Tests to check times before the custom start time.
+#[test]
+fn test_time_before_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let result = totp.get_otp_with_custom_time_start(0, 59);
+    assert_eq!(result, totp.get_otp_with_custom_time_start(59, 59));
+    assert_eq!(totp.time_remaining_with_start(0, 59), 30);
+}
+
+// Tests to check the SHA3 digests, with vectors generated by Python's hmac
+// and hashlib modules using the RFC6238 SHA256 and SHA512 secrets.
+#[cfg(feature = "sha3")]
+#[test]
+fn test_sha3_256() {
+    let totp = TOTP::new(SECRET_BYTES_SHA256, MacDigest::SHA3_256, 8, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 3503818);
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 384900);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_sha3_512() {
+    let totp = TOTP::new(SECRET_BYTES_SHA512, MacDigest::SHA3_512, 8, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 1892432);
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 25574199);
+}
+
+// Tests to check the truncated SHA-2 digests, with vectors generated by
+// Python's hmac and hashlib modules using the RFC6238 SHA256 and SHA512
+// secrets.
+#[test]
+fn test_sha224() {
+    let totp = TOTP::new(SECRET_BYTES_SHA256, MacDigest::SHA224, 8, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 8784232);
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 35844743);
+}
+
+#[test]
+fn test_sha384() {
+    let totp = TOTP::new(SECRET_BYTES_SHA512, MacDigest::SHA384, 8, 30);
+    assert_eq!(totp.get_otp(59).as_u32(), 3101971);
+    assert_eq!(totp.get_otp(1111111109).as_u32(), 67322300);
+}
+
+// Tests to check generation with a caller-supplied HMAC type.
+#[test]
+fn test_get_otp_generic() {
+    let totp = TOTP::new(SECRET_BYTES_SHA256, MacDigest::SHA1, 8, 30);
+    let result = totp.get_otp_generic::<Hmac<Sha256>>(59);
+    assert_eq!(result.as_u32(), 46119246);
+    assert_eq!(result.seconds_remaining(), Some(1));
+}
+
+// Tests to check serde support.
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    let totp = TOTP::new_from_base32(SECRET_BASE32_SHA256, MacDigest::SHA256, 8, 30)
+        .with_issuer("ACME Co");
+    let json = serde_json::to_string(&totp).unwrap();
+    assert!(json.contains(SECRET_BASE32_SHA256));
+
+    let deserialized: TOTP = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.get_issuer(), Some("ACME Co"));
+    assert_eq!(deserialized.get_otp(59), totp.get_otp(59));
+    assert_eq!(deserialized.get_otp(59).as_u32(), 46119246);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_invalid_secret() {
+    let json = r#"{"secret":"not base32!","mac_digest":"SHA1","digits":6,"period":30}"#;
+    assert!(serde_json::from_str::<TOTP>(json).is_err());
+}
+
+// Tests to check the secret isn't exposed in the Debug output.
+#[test]
+fn test_debug_redacts_secret() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let debug = format!("{:?}", totp);
+    assert!(debug.contains("period: 30"));
+    assert!(debug.contains("secret: [REDACTED; 20 bytes]"));
+    assert!(!debug.contains("49, 50, 51"));
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_zeroize_clone() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let cloned = totp.clone();
+    drop(totp);
+    assert_eq!(cloned.get_otp(59).as_u32(), 94287082);
+}
+
+// Tests to check generation with an injected clock.
+struct FixedClock(u64);
+
+impl Clock for FixedClock {
+    fn now_unix_secs(&self) -> u64 {
+        self.0
+    }
+}
+
+#[test]
+fn test_get_otp_with_clock() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let otp = totp.get_otp_with_clock(&FixedClock(1111111109));
+    assert_eq!(otp.as_u32(), 7081804);
+    assert_eq!(otp.seconds_remaining(), Some(1));
+}
+
+// Tests to check generation with an external time source.
+#[test]
+fn test_get_otp_with_source() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let otp = totp.get_otp_with_source(&FixedClock(1111111109));
+    assert_eq!(otp.as_u32(), 7081804);
+
+    let src: &dyn Clock = &FixedClock(59);
+    assert_eq!(totp.get_otp_with_source(src).as_u32(), 94287082);
+
+    let now = SystemClock.now_unix_secs();
+    let code = totp.get_otp_with_source(&SystemClock).as_u32();
+    assert!(code == totp.get_otp(now).as_u32() || code == totp.get_otp(now + 30).as_u32());
+}
+
+#[test]
+fn test_get_otp_now() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let now = SystemClock.now_unix_secs();
+    let code = totp.get_otp_now().as_u32();
+    assert!(code == totp.get_otp(now).as_u32() || code == totp.get_otp(now + 30).as_u32());
+}
+
+// Tests to check generation for a SystemTime or Duration.
+#[test]
+fn test_get_otp_at() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let when = UNIX_EPOCH + Duration::from_secs(1111111109);
+    assert_eq!(totp.get_otp_at(when).as_u32(), 7081804);
+    assert_eq!(totp.get_otp_at(when), totp.get_otp(1111111109));
+}
+
+#[test]
+fn test_get_otp_at_before_epoch() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let when = UNIX_EPOCH - Duration::from_secs(100);
+    assert_eq!(totp.get_otp_at(when), totp.get_otp(0));
+}
+
+#[test]
+fn test_get_otp_at_duration() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let otp =
+        totp.get_otp_at_duration(Duration::from_secs(1111111109) + Duration::from_millis(999));
+    assert_eq!(otp.as_u32(), 7081804);
+}
+
+#[test]
+fn test_get_otp_from_millis() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp_from_millis(59_000).as_u32(), 94287082);
+    assert_eq!(totp.get_otp_from_millis(59_999), totp.get_otp(59));
+    assert_eq!(
+        totp.get_otp_from_millis(1_111_111_109_999).as_u32(),
+        7081804
+    );
+}
+
+// Tests to check the time to counter mapping.
+#[test]
+fn test_counter_for_time() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.counter_for_time(59), 1);
+    assert_eq!(totp.counter_for_time(60), 2);
+    assert_eq!(totp.counter_for_time(1111111109), 0x23523EC);
+}
+
+#[test]
+fn test_counter_for_time_with_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.counter_for_time_with_start(100, 40), 2);
+    assert_eq!(totp.counter_for_time_with_start(10, 40), 0);
+}
+
+// Tests to check iterating over codes in a time range.
+#[test]
+fn test_codes_between() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let codes: Vec<_> = totp.codes_between(0, 90).collect();
+    assert_eq!(codes.len(), 3);
+    assert_eq!(codes[0], (0, totp.get_otp(0)));
+    assert_eq!(codes[1].0, 30);
+    assert_eq!(codes[2], (60, totp.get_otp(60)));
+}
+
+#[test]
+fn test_codes_between_empty() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.codes_between(60, 60).count(), 0);
+    assert_eq!(totp.codes_between(90, 60).count(), 0);
+}
+
+#[test]
+fn test_codes_between_short_range() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let codes: Vec<_> = totp.codes_between(59, 70).collect();
+    assert_eq!(codes.len(), 1);
+    assert_eq!(codes[0].1.as_u32(), 94287082);
+}
+
+// Tests to check the TOTP builder.
+#[test]
+fn test_builder_defaults() {
+    let totp = TOTP::builder()
+        .secret_bytes(SECRET_BYTES_SHA1)
+        .build()
+        .unwrap();
+    assert_eq!(totp.get_digest(), MacDigest::SHA1);
+    assert_eq!(totp.get_digits(), 6);
+    assert_eq!(totp.get_period(), 30);
+    assert_eq!(totp.get_issuer(), None);
+}
+
+#[test]
+fn test_builder_all_fields() {
+    let totp = TOTP::builder()
+        .secret_base32(SECRET_BASE32_SHA256)
+        .digest(MacDigest::SHA256)
+        .digits(8)
+        .period(30)
+        .issuer("ACME Co")
+        .account("john@example.com")
+        .build()
+        .unwrap();
+    assert_eq!(totp.get_otp(59).as_u32(), 46119246);
+    assert_eq!(totp.get_issuer(), Some("ACME Co"));
+    assert_eq!(totp.get_account(), Some("john@example.com"));
+}
+
+#[test]
+fn test_builder_errors() {
+    assert!(matches!(
+        TOTP::builder().build(),
+        Err(ParseError::MissingSecret)
+    ));
+    assert!(matches!(
+        TOTP::builder().secret_base32("not base32!").build(),
+        Err(ParseError::SecretParsingError(_))
+    ));
+    assert!(matches!(
+        TOTP::builder()
+            .secret_bytes(SECRET_BYTES_SHA1)
+            .digits(0)
+            .build(),
+        Err(ParseError::WrongDigitNumber { .. })
+    ));
+    assert!(matches!(
+        TOTP::builder()
+            .secret_bytes(SECRET_BYTES_SHA1)
+            .period(0)
+            .build(),
+        Err(ParseError::InvalidPeriod { .. })
+    ));
+}
+
+// Tests to check generation with a precomputed HMAC key.
+#[test]
+fn test_session_matches_get_otp() {
+    for digest in [MacDigest::SHA1, MacDigest::SHA256, MacDigest::SHA512] {
+        let totp = TOTP::new(SECRET_BYTES_SHA1, digest, 8, 30);
+        let session = totp.session();
+        for time in [59, 1111111109, 1234567890, 2000000000] {
+            assert_eq!(session.get_otp(time), totp.get_otp(time));
+        }
+        assert_eq!(
+            session.get_otp_with_custom_time_start(100, 40),
+            totp.get_otp_with_custom_time_start(100, 40)
+        );
+    }
+}
+
+#[test]
+fn test_session_rfc_vector() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.session().get_otp(1111111109).as_u32(), 7081804);
+}
+
+// Tests to check secrets are normalized like authenticator apps do.
+#[test]
+fn test_spaced_lowercase_secret() {
+    let canonical = TOTP::new_from_base32(SECRET_BASE32_SHA1, MacDigest::SHA1, 8, 30);
+    let spaced = TOTP::new_from_base32(
+        "gezd gnbv gy3t qojq gezd gnbv gy3t qojq",
+        MacDigest::SHA1,
+        8,
+        30,
+    );
+    for time in [59, 1111111109, 1234567890] {
+        assert_eq!(spaced.get_otp(time), canonical.get_otp(time));
+    }
+}
+
+// Tests to check the previous and next code previews.
+#[test]
+fn test_get_otp_previous_and_next() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp_previous(89).as_u32(), 94287082);
+    assert_eq!(totp.get_otp_next(29).as_u32(), 94287082);
+    assert_eq!(totp.get_otp_next(1111111079), totp.get_otp(1111111109));
+}
+
+#[test]
+fn test_get_otp_previous_near_epoch() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp_previous(10), totp.get_otp(0));
+}
+
+// Tests to check TOTP instances can be compared and used in sets.
+#[test]
+fn test_totp_eq() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(
+        totp,
+        TOTP::new_from_base32(SECRET_BASE32_SHA1, MacDigest::SHA1, 8, 30)
+    );
+    assert_ne!(totp, TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 60));
+    assert_ne!(totp, totp.clone().with_issuer("ACME Co"));
+    assert_ne!(totp, TOTP::new(b"another secret", MacDigest::SHA1, 8, 30));
+}
+
+#[test]
+fn test_totp_hash_set() {
+    let mut set = std::collections::HashSet::new();
+    set.insert(TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30));
+    set.insert(TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30));
+    set.insert(TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA256, 8, 30));
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_totp_hash_set_period() {
+    let mut set = std::collections::HashSet::new();
+    set.insert(TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30));
+    set.insert(TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 60));
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 60)));
+}
+
+// Tests to check the elapsed fraction of a period.
+#[test]
+fn test_progress() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.progress(60), 0.0);
+    assert_eq!(totp.progress(75), 0.5);
+    assert_eq!(totp.progress(89), 29.0 / 30.0);
+    assert_eq!(totp.progress(90), 0.0);
+}
+
+// Tests to check results carry the time step they were generated from.
+#[test]
+fn test_result_counter() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp(59).counter(), 1);
+    assert_eq!(totp.get_otp(1111111109).counter(), 37037036);
+    assert_eq!(totp.get_otp_with_custom_time_start(100, 40).counter(), 2);
+    assert_eq!(totp.session().get_otp(59).counter(), 1);
+}
+
+// Tests to check the default constants match the default constructors.
+#[test]
+fn test_default_constants() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1);
+    assert_eq!(totp.get_digits(), DEFAULT_DIGITS);
+    assert_eq!(totp.get_period(), DEFAULT_PERIOD);
+}
+
+#[test]
+fn test_builder_default() {
+    let totp = TotpBuilder::default()
+        .secret_bytes(SECRET_BYTES_SHA1)
+        .build()
+        .unwrap();
+    assert_eq!(totp.get_digest(), MacDigest::SHA1);
+    assert_eq!(totp.get_digits(), DEFAULT_DIGITS);
+    assert_eq!(totp.get_period(), DEFAULT_PERIOD);
+}
+
+// Tests to check owned secrets can be moved into an instance.
+#[test]
+fn test_from_secret_vec() {
+    let totp = TOTP::from_secret_vec(SECRET_BYTES_SHA1.to_vec(), MacDigest::SHA1, 8, 30);
+    assert_eq!(totp, TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30));
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+}
+
+// Tests to check the absolute expiry of a code.
+#[test]
+fn test_expires_at() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let now = UNIX_EPOCH + Duration::from_millis(59_500);
+    assert_eq!(totp.expires_at(now), UNIX_EPOCH + Duration::from_secs(60));
+    assert_eq!(
+        totp.expires_at(UNIX_EPOCH + Duration::from_secs(60)),
+        UNIX_EPOCH + Duration::from_secs(90)
+    );
+    assert_eq!(
+        totp.expires_at(UNIX_EPOCH - Duration::from_secs(5)),
+        UNIX_EPOCH + Duration::from_secs(30)
+    );
+}
+
+#[test]
+fn test_expires_at_with_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let now = UNIX_EPOCH + Duration::from_secs(100);
+    assert_eq!(
+        totp.expires_at_with_start(now, 10),
+        UNIX_EPOCH + Duration::from_secs(130)
+    );
+}
+
+#[test]
+fn test_expires_at_before_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let now = UNIX_EPOCH + Duration::from_secs(100);
+    assert_eq!(
+        totp.expires_at_with_start(now, 1000),
+        UNIX_EPOCH + Duration::from_secs(1030)
+    );
+}
+
+// Tests to check the fallible generation returns errors rather than panicking.
+#[test]
+fn test_try_get_otp() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.try_get_otp(59), Ok(totp.get_otp(59)));
+
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 0);
+    assert_eq!(totp.try_get_otp(59), Err(OtpError::InvalidPeriod));
+}
+
+// Tests to check secrets can be decoded with the base32hex alphabet.
+#[test]
+fn test_new_from_base32_with_alphabet() {
+    let totp = TOTP::new_from_base32_with_alphabet(
+        "64P36D1L6ORJGE9G64P36D1L6ORJGE9G",
+        Base32Alphabet::Rfc4648Hex,
+        MacDigest::SHA1,
+        8,
+        30,
+    );
+    assert_eq!(totp.get_otp(59).as_u32(), 94287082);
+    assert_eq!(
+        TOTP::try_from_base32_with_alphabet(
+            SECRET_BASE32_SHA1,
+            Base32Alphabet::Rfc4648,
+            MacDigest::SHA1,
+            8,
+            30
+        )
+        .unwrap(),
+        TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30)
+    );
+}
+
+// Tests to check the raw HMAC is as long as the digest output.
+#[test]
+fn test_hmac_for_time() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let hotp = HOTP::new(SECRET_BYTES_SHA1, 8);
+    assert_eq!(totp.hmac_for_time(59), hotp.hmac_for_counter(1));
+
+    let totp = TOTP::new(SECRET_BYTES_SHA512, MacDigest::SHA512, 8, 30);
+    assert_eq!(totp.hmac_for_time(59).len(), 64);
+}
+
+// Tests to check the truncation offset can be fixed for legacy tokens.
+#[test]
+fn test_get_otp_with_offset() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp.get_otp_with_offset(59, None), totp.get_otp(59));
+    assert_ne!(totp.get_otp_with_offset(59, Some(0)), totp.get_otp(59));
+    assert_eq!(totp.get_otp_with_offset(0, Some(0)), totp.get_otp(0));
+}
+
+// Tests to check a truncation offset past the end of the hash panics.
+#[test]
+#[should_panic]
+fn test_get_otp_with_offset_out_of_range() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    totp.get_otp_with_offset(59, Some(17));
+}
+
+// Tests to check the minimum secret length is enforced when requested.
+#[test]
+fn test_try_new_checked() {
+    assert!(TOTP::try_new_checked(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30).is_ok());
+    assert!(matches!(
+        TOTP::try_new_checked(b"secret", MacDigest::SHA1, 6, 30),
+        Err(ParseError::SecretTooShort(6))
+    ));
+    assert!(matches!(
+        TOTP::try_new_checked(SECRET_BYTES_SHA1, MacDigest::SHA1, 6, 0),
+        Err(ParseError::InvalidPeriod { .. })
+    ));
+}
+
+// Tests to check an empty secret is rejected by the fallible constructors.
+#[test]
+fn test_try_new_empty_secret() {
+    assert!(matches!(
+        TOTP::try_new(b"", MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::try_new_checked(b"", MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::builder().secret_bytes(b"").build(),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+#[test]
+fn test_decoding_constructors_empty_secret() {
+    assert!(matches!(
+        TOTP::try_from_base32("", MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::try_from_base32_with_alphabet("", Base32Alphabet::Crockford, MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::try_default_from_base32(""),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::new_from_hex("", MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::default_from_hex(""),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn test_base64_empty_secret() {
+    assert!(matches!(
+        TOTP::new_from_base64("", MacDigest::SHA1, 6, 30),
+        Err(ParseError::EmptySecret)
+    ));
+    assert!(matches!(
+        TOTP::default_from_base64(""),
+        Err(ParseError::EmptySecret)
+    ));
+}
+
+// Tests to check a start time stored on the instance.
+#[test]
+fn test_stored_time_start() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let offset = totp.clone().with_time_start(100);
+    assert_eq!(totp.get_time_start(), 0);
+    assert_eq!(offset.get_time_start(), 100);
+
+    assert_eq!(
+        offset.get_otp(159),
+        totp.get_otp_with_custom_time_start(159, 100)
+    );
+    assert_eq!(offset.get_otp(159).as_u32(), 94287082);
+    assert_eq!(offset.session().get_otp(159), offset.get_otp(159));
+    assert_eq!(offset.counter_for_time(160), 2);
+    assert_eq!(offset.time_remaining(110), 20);
+    assert_eq!(offset.progress(115), 0.5);
+    assert!(offset.verify(94287082, 159));
+    assert!(!totp.verify(94287082, 159));
+}
+
+#[test]
+fn test_builder_time_start() {
+    let totp = TOTP::builder()
+        .secret_bytes(SECRET_BYTES_SHA1)
+        .digits(8)
+        .time_start(100)
+        .build()
+        .unwrap();
+    assert_eq!(totp.get_time_start(), 100);
+    assert_eq!(totp.get_otp(159).as_u32(), 94287082);
+}
+
+// Tests to check the codes within a verification window.
+#[test]
+fn test_window_codes() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let codes = totp.window_codes(1111111109, 1);
+    assert_eq!(codes.len(), 3);
+    assert_eq!(codes[0].as_u32(), totp.get_otp(1111111079).as_u32());
+    assert_eq!(codes[1].as_u32(), 7081804);
+    assert_eq!(codes[2].as_u32(), totp.get_otp(1111111139).as_u32());
+    assert_eq!(codes[1].counter(), totp.counter_for_time(1111111109));
+    for code in &codes {
+        assert!(totp.verify_with_skew(code.as_u32(), 1111111109, 1));
+    }
+    assert_eq!(totp.window_codes(1111111109, 0).len(), 1);
+}
+
+#[test]
+fn test_window_codes_near_epoch() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let codes = totp.window_codes(59, 2);
+    assert_eq!(codes.len(), 4);
+    assert_eq!(codes[0].counter(), 0);
+    assert_eq!(codes[1].as_u32(), 94287082);
+}
+
+// Tests to check codes are only accepted once.
+#[test]
+fn test_verify_once() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    let step = totp.counter_for_time(1111111109);
+
+    let accepted = totp.verify_once(7081804, 1111111109, 1, None);
+    assert_eq!(accepted, Some(step));
+    assert_eq!(totp.verify_once(7081804, 1111111109, 1, accepted), None);
+    assert_eq!(totp.verify_once(7081804, 1111111120, 1, accepted), None);
+    assert_eq!(
+        totp.verify_once(7081804, 1111111109, 1, Some(step - 1)),
+        Some(step)
+    );
+    assert_eq!(totp.verify_once(1234, 1111111109, 1, None), None);
+
+    let next = totp.get_otp(1111111139).as_u32();
+    assert_eq!(
+        totp.verify_once(next, 1111111109, 1, accepted),
+        Some(step + 1)
+    );
+}
+
+// Tests to check the Display summary doesn't expose the secret.
+#[test]
+fn test_display() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 6, 30);
+    assert_eq!(totp.to_string(), "TOTP(SHA1, 6 digits, 30s)");
+    let totp = TOTP::new(SECRET_BYTES_SHA256, MacDigest::SHA256, 8, 60);
+    assert_eq!(totp.to_string(), "TOTP(SHA256, 8 digits, 60s)");
+}
+
+#[test]
+fn test_display_steam() {
+    let totp = TOTP::default_from_utf8(SECRET_UTF8_SHA1).with_encoding(OtpEncoding::Steam);
+    assert_eq!(totp.to_string(), "TOTP(SHA1, Steam, 30s)");
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_display_sha3() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA3_256, 6, 30);
+    assert_eq!(totp.to_string(), "TOTP(SHA3-256, 6 digits, 30s)");
+}
+
+// Tests to check the period can be given as a Duration.
+#[test]
+fn test_with_period_duration() {
+    let totp = TOTP::with_period_duration(
+        SECRET_BYTES_SHA1,
+        MacDigest::SHA1,
+        8,
+        Duration::from_secs(60),
+    )
+    .unwrap();
+    assert_eq!(totp, TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 60));
+
+    let invalid = [
+        (Duration::ZERO, ValueError::OutOfRange),
+        (Duration::from_millis(500), ValueError::OutOfRange),
+        (Duration::from_millis(30_500), ValueError::Fractional),
+    ];
+    for (period, expected) in invalid {
+        assert!(matches!(
+            TOTP::with_period_duration(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, period),
+            Err(ParseError::InvalidPeriod { reason, .. }) if reason == expected
+        ));
+    }
+}
+
+// Tests to check the detailed verification result.
+#[test]
+fn test_verify_detailed() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert_eq!(
+        totp.verify_detailed(7081804, 1111111109, 1),
+        VerifyResult::Valid
+    );
+    assert_eq!(
+        totp.verify_detailed(7081804, 1111111139, 1),
+        VerifyResult::ValidWithinSkew(-1)
+    );
+    assert_eq!(
+        totp.verify_detailed(7081804, 1111111079, 1),
+        VerifyResult::ValidWithinSkew(1)
+    );
+    assert_eq!(
+        totp.verify_detailed(7081804, 1111111169, 1),
+        VerifyResult::Invalid
+    );
+    assert!(VerifyResult::ValidWithinSkew(1).is_valid());
+    assert!(!VerifyResult::Invalid.is_valid());
+}
+
+// Tests to check the base64 constructors.
+#[cfg(feature = "base64")]
+#[test]
+fn test_new_from_base64() {
+    let standard =
+        TOTP::new_from_base64("MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=", MacDigest::SHA1, 8, 30).unwrap();
+    assert_eq!(standard.get_secret(), SECRET_BYTES_SHA1);
+    assert_eq!(standard.get_otp(59).as_u32(), 94287082);
+    assert_eq!(standard.get_secret_base64(), "MTIzNDU2Nzg5MDEyMzQ1Njc4OTA=");
+
+    let unpadded = TOTP::default_from_base64("MTIzNDU2Nzg5MDEyMzQ1Njc4OTA").unwrap();
+    assert_eq!(unpadded.get_secret(), SECRET_BYTES_SHA1);
+
+    assert!(matches!(
+        TOTP::default_from_base64("not base64!"),
+        Err(ParseError::SecretParsingError(_))
+    ));
+}
+
+// Tests to check the enrollment QR codes are rendered in both formats.
+#[cfg(feature = "qr")]
+#[test]
+fn test_to_qr() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 6, 30);
+
+    let svg = totp.to_qr_svg("ACME Co:john.doe@email.com", Some("ACME Co"));
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.contains("<svg"));
+
+    let png = totp.to_qr_png("ACME Co:john.doe@email.com", Some("ACME Co"));
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+}
+
+// Tests to check arbitrary instances stay within the valid parameter ranges.
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    for seed in [
+        &[][..],
+        &[0xff; 64],
+        b"an arbitrary fuzzer input of some length",
+    ] {
+        let totp = TOTP::arbitrary(&mut Unstructured::new(seed)).unwrap();
+        assert!(!totp.get_secret().is_empty());
+        assert!((1..=10).contains(&totp.get_digits()));
+        assert!(totp.get_period() > 0);
+        totp.get_otp(59);
+    }
+}
+
+// Tests to check instances built from a config.
+#[test]
+fn test_from_config() {
+    let default = TotpConfig::default();
+    assert_eq!(default.mac_digest, MacDigest::SHA1);
+    assert_eq!(default.digits, DEFAULT_DIGITS);
+    assert_eq!(default.period, DEFAULT_PERIOD);
+    assert_eq!(default.time_start, 0);
+    assert_eq!(
+        TOTP::from_config(SECRET_BYTES_SHA1, &default),
+        TOTP::default_from_secret(SECRET_BYTES_SHA1)
+    );
+
+    let config = TotpConfig {
+        digits: 8,
+        time_start: 30,
+        ..TotpConfig::default()
+    };
+    let totp = TOTP::from_config(SECRET_BYTES_SHA1, &config);
+    assert_eq!(totp.get_time_start(), 30);
+    assert_eq!(totp.get_otp(89).as_u32(), 94287082);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_config_serde_defaults() {
+    let config: TotpConfig = serde_json::from_str(r#"{"digits": 8}"#).unwrap();
+    assert_eq!(config.digits, 8);
+    assert_eq!(config.period, DEFAULT_PERIOD);
+    assert_eq!(config.mac_digest, MacDigest::SHA1);
+}
+
+// Tests to check verification of a code wrapped in an OTPResult.
+#[test]
+fn test_verify_result() {
+    let totp = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30);
+    assert!(totp.verify_result(&OTPResult::new(8, 94287082), 59, 0));
+    assert!(totp.verify_result(&OTPResult::new(8, 7081804), 1111111109, 0));
+    assert!(totp.verify_result(&OTPResult::new(8, 94287082), 89, 1));
+    assert!(!totp.verify_result(&OTPResult::new(8, 94287082), 89, 0));
+    assert!(!totp.verify_result(&OTPResult::new(8, 94287083), 59, 0));
+
+    // A matching code with a different digit count is rejected
+    assert!(!totp.verify_result(&OTPResult::new(9, 94287082), 59, 0));
+    assert!(!totp.verify_result(&OTPResult::new(7, 4287082), 59, 0));
+}
+
+// Tests to check the binary layout round-trips.
+#[test]
+fn test_bytes_round_trip() {
+    let totp = TOTP::new(SECRET_BYTES_SHA256, MacDigest::SHA256, 8, 60).with_time_start(30);
+    let bytes = totp.to_bytes();
+    assert_eq!(bytes.len(), 1 + 3 + 8 + 8 + 4 + SECRET_BYTES_SHA256.len());
+    assert_eq!(bytes[..4], [1, 1, 0, 8]);
+    assert_eq!(TOTP::from_bytes(&bytes).unwrap(), totp);
+
+    let steam = TOTP::default_from_secret(SECRET_BYTES_SHA1).with_encoding(OtpEncoding::Steam);
+    let parsed = TOTP::from_bytes(&steam.to_bytes()).unwrap();
+    assert_eq!(parsed.get_encoding(), OtpEncoding::Steam);
+    assert_eq!(parsed.get_otp(59), steam.get_otp(59));
+}
+
+#[test]
+fn test_from_bytes_invalid() {
+    let bytes = TOTP::new(SECRET_BYTES_SHA1, MacDigest::SHA1, 8, 30).to_bytes();
+    assert!(matches!(
+        TOTP::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ParseError::InvalidBytes)
+    ));
+    assert!(matches!(
+        TOTP::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+        Err(ParseError::InvalidBytes)
+    ));
+    assert!(matches!(
+        TOTP::from_bytes(&[]),
+        Err(ParseError::InvalidBytes)
+    ));
+
+    let mut modified = bytes.clone();
+    modified[0] = 2;
+    assert!(matches!(
+        TOTP::from_bytes(&modified),
+        Err(ParseError::InvalidBytes)
+    ));
+
+    let mut modified = bytes.clone();
+    modified[1] = 42;
+    assert!(matches!(
+        TOTP::from_bytes(&modified),
+        Err(ParseError::UnknownAlgorithm(_))
+    ));
+
+    let mut modified = bytes;
+    modified[3] = 11;
+    assert!(matches!(
+        TOTP::from_bytes(&modified),
+        Err(ParseError::WrongDigitNumber { .. })
+    ));
+}
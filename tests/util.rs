@@ -1,6 +1,15 @@
+use xotp::hotp::HOTP;
+use xotp::totp::TOTP;
 use xotp::util::ParseError;
 use xotp::util::ParseResult;
-use xotp::util::{parse_otpauth_uri, MacDigest};
+use xotp::util::{
+    base32_decode, base32_decode_with_alphabet, base32_encode, constant_time_eq_pairs,
+    dynamic_truncate, generate_batch, hash_with_name, hotp_code, otp_type, parse_otpauth_uri,
+    parse_otpauth_uri_full, totp_code, validate_base32_secret, Base32Alphabet, MacDigest, OtpType,
+    ValueError,
+};
+#[cfg(feature = "migration")]
+use xotp::util::{build_migration_uri, parse_migration_uri, OtpEncoding};
 
 // Examples
 // otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example
@@ -23,6 +32,7 @@ fn test_otpauth_parse_invalid_scheme() {
 }
 
 #[test]
+#[allow(clippy::unneeded_struct_pattern)]
 fn test_otpauth_parse_missing_otp_type() {
     let res = parse_otpauth_uri(
         "otpauth:///Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example",
@@ -47,12 +57,21 @@ fn test_otpauth_parse_invalid_otp_type() {
 }
 
 #[test]
+#[allow(clippy::unneeded_struct_pattern)]
 fn test_otpauth_parse_missing_secret() {
     let res = parse_otpauth_uri("otpauth://totp/Example:alice@google.com?issuer=Example");
     assert!(res.is_err());
     assert!(matches!(res.unwrap_err(), ParseError::MissingSecret { .. }));
 }
 
+#[test]
+fn test_otpauth_parse_empty_secret() {
+    let res = parse_otpauth_uri("otpauth://totp/a?secret=");
+    assert!(matches!(res, Err(ParseError::EmptySecret)));
+    let res = parse_otpauth_uri("otpauth://hotp/a?secret=&counter=0");
+    assert!(matches!(res, Err(ParseError::EmptySecret)));
+}
+
 #[test]
 fn test_otpauth_parse_invalid_digits() {
     let res = parse_otpauth_uri(
@@ -77,6 +96,22 @@ fn test_otpauth_parse_invalid_digits_2() {
     ));
 }
 
+#[test]
+fn test_otpauth_parse_too_many_digits() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example&digits=11",
+    );
+    assert!(matches!(
+        res,
+        Err(ParseError::WrongDigitNumber { value, reason: ValueError::OutOfRange }) if value == "11"
+    ));
+
+    let res = parse_otpauth_uri(
+        "otpauth://hotp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&digits=1000000000&counter=0",
+    );
+    assert!(matches!(res, Err(ParseError::WrongDigitNumber { .. })));
+}
+
 #[test]
 fn test_otpauth_parse_totp_with_defaults() {
     let res = parse_otpauth_uri(
@@ -139,7 +174,27 @@ fn test_otpauth_parse_totp_invalid_period_2() {
     assert!(matches!(res.unwrap_err(), ParseError::InvalidPeriod { .. }));
 }
 
+// Tests to check invalid digit counts and periods carry why they were rejected.
 #[test]
+fn test_otpauth_parse_value_error_reason() {
+    let reason = |uri: &str| match parse_otpauth_uri(uri) {
+        Err(ParseError::WrongDigitNumber { reason, .. }) => Some(reason),
+        Err(ParseError::InvalidPeriod { reason, .. }) => Some(reason),
+        _ => None,
+    };
+    let base = "otpauth://totp/ACME?secret=JBSWY3DPEHPK3PXP";
+    assert_eq!(reason(&format!("{}&digits=abc", base)), Some(ValueError::NotANumber));
+    assert_eq!(reason(&format!("{}&digits=-6", base)), Some(ValueError::NotANumber));
+    assert_eq!(reason(&format!("{}&digits=0", base)), Some(ValueError::OutOfRange));
+    assert_eq!(reason(&format!("{}&digits=6.5", base)), Some(ValueError::Fractional));
+    assert_eq!(reason(&format!("{}&period=1.5", base)), Some(ValueError::Fractional));
+    assert_eq!(reason(&format!("{}&period=NaN", base)), Some(ValueError::NotANumber));
+    assert_eq!(reason(&format!("{}&period=0", base)), Some(ValueError::OutOfRange));
+    assert_eq!(reason(&format!("{}&period=30", base)), None);
+}
+
+#[test]
+#[allow(clippy::unneeded_struct_pattern)]
 fn test_otpauth_parse_hotp_missing_counter() {
     let res = parse_otpauth_uri(
         "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
@@ -185,3 +240,808 @@ fn test_otpauth_parse_hotp_with_digits() {
         panic!();
     }
 }
+
+#[test]
+fn test_hotp_to_uri() {
+    let hotp = HOTP::new_from_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ", 8);
+    assert_eq!(
+        hotp.to_uri("ACME Co:john.doe@email.com", Some("ACME Co"), 1234),
+        "otpauth://hotp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME+Co&digits=8&counter=1234"
+    );
+}
+
+#[test]
+fn test_hotp_to_uri_round_trip() {
+    let hotp = HOTP::new_from_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ", 8);
+    let res = parse_otpauth_uri(&hotp.to_uri("john.doe@email.com", None, 1234));
+    if let Ok(ParseResult::HOTP(parsed, counter)) = res {
+        assert_eq!(parsed.get_digits(), 8);
+        assert_eq!(counter, 1234);
+        assert_eq!(parsed.get_otp(counter), hotp.get_otp(counter));
+    } else {
+        panic!();
+    }
+}
+
+#[test]
+fn test_totp_to_uri_round_trip() {
+    let totp = TOTP::new_from_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ", MacDigest::SHA256, 8, 60);
+    let res = parse_otpauth_uri(&totp.to_uri("ACME Co:john.doe@email.com", Some("ACME Co")));
+    if let Ok(ParseResult::TOTP(parsed)) = res {
+        assert_eq!(parsed.get_digest(), MacDigest::SHA256);
+        assert_eq!(parsed.get_digits(), 8);
+        assert_eq!(parsed.get_period(), 60);
+        assert_eq!(parsed.get_otp(59), totp.get_otp(59));
+    } else {
+        panic!();
+    }
+}
+
+// Tests to check the Steam encoding survives a URI round trip.
+#[test]
+fn test_totp_to_uri_round_trip_steam() {
+    use xotp::util::OtpEncoding;
+
+    let totp = TOTP::new(b"12345678901234567890", MacDigest::SHA1, 5, 30)
+        .with_encoding(OtpEncoding::Steam);
+    let uri = totp.to_uri("Steam:john", None);
+    assert!(uri.contains("encoder=steam"));
+    let parsed = parse_otpauth_uri(&uri).unwrap().into_totp().unwrap();
+    assert_eq!(parsed.get_encoding(), OtpEncoding::Steam);
+    assert!(parsed.get_extras().is_empty());
+    assert_eq!(parsed.get_otp(59).as_string(), totp.get_otp(59).as_string());
+
+    assert!(matches!(
+        parse_otpauth_uri("otpauth://totp/ACME?secret=GEZDGNBVGY3TQOJQ&encoder=base64"),
+        Err(ParseError::UnsupportedEncoding(x)) if x == "base64"
+    ));
+}
+
+#[test]
+fn test_otpauth_parse_label() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co",
+    )
+    .unwrap();
+    assert_eq!(res.issuer(), Some("ACME Co"));
+    assert_eq!(res.account(), Some("john.doe@email.com"));
+}
+
+#[test]
+fn test_otpauth_parse_label_without_issuer_param() {
+    let res = parse_otpauth_uri(
+        "otpauth://hotp/ACME%20Co%3A%20john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&counter=0",
+    )
+    .unwrap();
+    assert_eq!(res.issuer(), Some("ACME Co"));
+    assert_eq!(res.account(), Some("john.doe@email.com"));
+}
+
+#[test]
+fn test_otpauth_parse_label_without_prefix() {
+    let res =
+        parse_otpauth_uri("otpauth://totp/alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Example")
+            .unwrap();
+    assert_eq!(res.issuer(), Some("Example"));
+    assert_eq!(res.account(), Some("alice@google.com"));
+}
+
+// Tests to check a custom start time is read from and written to the t0 parameter.
+#[test]
+fn test_otpauth_parse_time_start() {
+    let totp = parse_otpauth_uri("otpauth://totp/ACME?secret=GEZDGNBVGY3TQOJQ&t0=100")
+        .unwrap()
+        .into_totp()
+        .unwrap();
+    assert_eq!(totp.get_time_start(), 100);
+    assert!(totp.get_extras().is_empty());
+    assert_eq!(totp.get_otp(159), totp.get_otp_with_custom_time_start(159, 100));
+
+    let uri = totp.to_uri("ACME", None);
+    assert!(uri.contains("t0=100"));
+    let parsed = parse_otpauth_uri(&uri).unwrap().into_totp().unwrap();
+    assert_eq!(parsed.get_time_start(), 100);
+
+    let totp = parse_otpauth_uri("otpauth://totp/ACME?secret=GEZDGNBVGY3TQOJQ")
+        .unwrap()
+        .into_totp()
+        .unwrap();
+    assert_eq!(totp.get_time_start(), 0);
+    assert!(!totp.to_uri("ACME", None).contains("t0"));
+    assert!(!totp.to_uri("ACME", None).contains("encoder"));
+
+    assert!(matches!(
+        parse_otpauth_uri("otpauth://totp/ACME?secret=GEZDGNBVGY3TQOJQ&t0=-5"),
+        Err(ParseError::InvalidTimeStart(_))
+    ));
+}
+
+// Tests to check lowercase, padded or spaced secrets are normalized.
+#[test]
+fn test_otpauth_parse_unnormalized_secret() {
+    for secret in [
+        "gezdgnbvgy3tqojq",
+        "gezdgnbvgy3tqojq%3D%3D%3D",
+        "GEZDGNBVGY3TQOJQ===",
+        "gezd%20gnbv%20gy3t%20qojq",
+    ] {
+        let uri = format!("otpauth://totp/ACME?secret={}", secret);
+        let totp = parse_otpauth_uri(&uri).unwrap().into_totp().unwrap();
+        assert_eq!(totp.get_secret(), b"1234567890");
+    }
+}
+
+// Tests to check an empty or missing label isn't read as the OTP type.
+#[test]
+fn test_otpauth_parse_empty_label() {
+    for uri in [
+        "otpauth://totp/?secret=JBSWY3DPEHPK3PXP",
+        "otpauth://totp?secret=JBSWY3DPEHPK3PXP",
+    ] {
+        let res = parse_otpauth_uri(uri).unwrap();
+        assert!(res.is_totp());
+        assert_eq!(res.issuer(), None);
+        assert_eq!(res.account(), None);
+    }
+
+    let res = parse_otpauth_uri("otpauth://hotp?secret=JBSWY3DPEHPK3PXP&issuer=Example&counter=0")
+        .unwrap();
+    assert!(res.is_hotp());
+    assert_eq!(res.issuer(), Some("Example"));
+    assert_eq!(res.account(), None);
+
+    assert!(matches!(
+        parse_otpauth_uri("otpauth:///totp?secret=JBSWY3DPEHPK3PXP"),
+        Err(ParseError::MissingOtpType)
+    ));
+}
+
+// Tests to check only the first colon of a label separates the issuer.
+#[test]
+fn test_otpauth_parse_label_with_colon() {
+    let res = parse_otpauth_uri("otpauth://totp/ACME:john:doe?secret=JBSWY3DPEHPK3PXP").unwrap();
+    assert_eq!(res.issuer(), Some("ACME"));
+    assert_eq!(res.account(), Some("john:doe"));
+
+    let res = parse_otpauth_uri("otpauth://totp/ACME:?secret=JBSWY3DPEHPK3PXP").unwrap();
+    assert_eq!(res.issuer(), Some("ACME"));
+    assert_eq!(res.account(), None);
+
+    let res = parse_otpauth_uri("otpauth://totp/:john?secret=JBSWY3DPEHPK3PXP").unwrap();
+    assert_eq!(res.issuer(), None);
+    assert_eq!(res.account(), Some("john"));
+}
+
+#[test]
+fn test_otpauth_parse_issuer_mismatch() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/Example:alice@google.com?secret=JBSWY3DPEHPK3PXP&issuer=Other",
+    );
+    assert!(matches!(
+        res.unwrap_err(),
+        ParseError::IssuerMismatch { .. }
+    ));
+}
+
+#[test]
+fn test_totp_labeled_uri_round_trip() {
+    let totp = TOTP::default_from_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ")
+        .with_issuer("ACME Co")
+        .with_account("john.doe@email.com");
+    if let Ok(ParseResult::TOTP(parsed)) = parse_otpauth_uri(&totp.to_labeled_uri()) {
+        assert_eq!(parsed.get_issuer(), Some("ACME Co"));
+        assert_eq!(parsed.get_account(), Some("john.doe@email.com"));
+    } else {
+        panic!();
+    }
+}
+
+#[test]
+fn test_totp_labeled_uri_round_trip_colons() {
+    let labels = [
+        (Some("A:B"), Some("x")),
+        (Some("ACME"), Some("x:y")),
+        (Some("50%3A off"), Some("100%")),
+        (None, Some("a:b")),
+        (None, Some("john")),
+    ];
+    for (issuer, account) in labels {
+        let mut totp = TOTP::default_from_base32("HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ");
+        if let Some(issuer) = issuer {
+            totp = totp.with_issuer(issuer);
+        }
+        if let Some(account) = account {
+            totp = totp.with_account(account);
+        }
+        match parse_otpauth_uri(&totp.to_labeled_uri()) {
+            Ok(ParseResult::TOTP(parsed)) => {
+                assert_eq!(parsed.get_issuer(), issuer);
+                assert_eq!(parsed.get_account(), account);
+            }
+            res => panic!("{:?}", res),
+        }
+    }
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_otpauth_parse_totp_sha3() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA3-512",
+    );
+    if let Ok(ParseResult::TOTP(totp)) = res {
+        assert_eq!(totp.get_digest(), MacDigest::SHA3_512);
+    } else {
+        panic!();
+    }
+}
+
+// Tests to check unrecognized query parameters are preserved.
+#[test]
+fn test_otpauth_parse_extras() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP&issuer=ACME&image=https%3A%2F%2Facme.co%2Flogo.png&lock=true",
+    )
+    .unwrap();
+    assert_eq!(res.extras().len(), 2);
+    assert_eq!(res.extras()["image"], "https://acme.co/logo.png");
+    assert_eq!(res.extras()["lock"], "true");
+
+    if let ParseResult::TOTP(totp) = res {
+        let reparsed = parse_otpauth_uri(&totp.to_labeled_uri()).unwrap();
+        assert_eq!(reparsed.extras(), totp.get_extras());
+    } else {
+        panic!();
+    }
+}
+
+#[test]
+fn test_otpauth_parse_extras_hotp() {
+    let res = parse_otpauth_uri(
+        "otpauth://hotp/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=2&image=logo.png",
+    )
+    .unwrap();
+    assert_eq!(res.extras()["image"], "logo.png");
+
+    if let ParseResult::HOTP(hotp, counter) = res {
+        let uri = hotp.to_uri("ACME:john", None, counter);
+        assert!(uri.ends_with("&counter=2&image=logo.png"));
+    } else {
+        panic!();
+    }
+}
+
+#[test]
+fn test_otpauth_parse_no_extras() {
+    let res = parse_otpauth_uri(
+        "otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP&issuer=ACME&algorithm=SHA1&digits=6&period=30",
+    )
+    .unwrap();
+    assert!(res.extras().is_empty());
+}
+
+// Tests to check the metadata is returned alongside the parsed instance.
+#[test]
+fn test_otpauth_parse_full() {
+    let auth = parse_otpauth_uri_full(
+        "otpauth://totp/ACME%20Co:john.doe@email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&image=https%3A%2F%2Fexample.com%2Flogo.png",
+    )
+    .unwrap();
+    assert_eq!(auth.issuer.as_deref(), Some("ACME Co"));
+    assert_eq!(auth.account.as_deref(), Some("john.doe@email.com"));
+    assert_eq!(
+        auth.extras.get("image").map(String::as_str),
+        Some("https://example.com/logo.png")
+    );
+    assert!(auth.result.is_totp());
+    assert_eq!(auth.result.issuer(), Some("ACME Co"));
+
+    let auth = parse_otpauth_uri_full("otpauth://hotp?secret=JBSWY3DPEHPK3PXP&counter=3").unwrap();
+    assert_eq!(auth.issuer, None);
+    assert_eq!(auth.account, None);
+    assert!(auth.extras.is_empty());
+    assert!(matches!(auth.result, ParseResult::HOTP(_, 3)));
+
+    assert!(matches!(
+        parse_otpauth_uri_full("otpauth://xotp/ACME?secret=JBSWY3DPEHPK3PXP"),
+        Err(ParseError::UnknownOtpType(_))
+    ));
+}
+
+// Tests to check URIs can be parsed through the standard conversion traits.
+#[test]
+fn test_otpauth_parse_traits() {
+    let uri = "otpauth://hotp/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=4";
+    let parsed: ParseResult = uri.parse().unwrap();
+    assert!(matches!(parsed, ParseResult::HOTP(_, 4)));
+
+    let converted: ParseResult = uri.try_into().unwrap();
+    assert_eq!(converted.account(), Some("john"));
+
+    assert!(matches!(
+        ParseResult::try_from("auth://totp/ACME?secret=JBSWY3DPEHPK3PXP"),
+        Err(ParseError::WrongScheme(_))
+    ));
+}
+
+// Tests to check parsed URIs survive a serde round-trip, counter included.
+#[cfg(feature = "serde")]
+#[test]
+fn test_parse_result_serde_round_trip() {
+    let uri = "otpauth://hotp/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=7&foo=bar";
+    let json = serde_json::to_string(&parse_otpauth_uri(uri).unwrap()).unwrap();
+    let parsed: ParseResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.account(), Some("john"));
+    let (hotp, counter) = parsed.into_hotp().unwrap();
+    assert_eq!(counter, 7);
+    assert_eq!(hotp.get_otp(7), HOTP::default_from_base32("JBSWY3DPEHPK3PXP").get_otp(7));
+
+    let uri = "otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP&period=60&foo=bar";
+    let json = serde_json::to_string(&parse_otpauth_uri_full(uri).unwrap()).unwrap();
+    let auth: xotp::util::OtpAuth = serde_json::from_str(&json).unwrap();
+    assert_eq!(auth.issuer.as_deref(), Some("ACME"));
+    assert_eq!(auth.extras.get("foo").map(String::as_str), Some("bar"));
+    let totp = auth.result.into_totp().unwrap();
+    let expected = TOTP::new_from_base32("JBSWY3DPEHPK3PXP", MacDigest::SHA1, 6, 60);
+    assert_eq!(totp.get_otp(59), expected.get_otp(59));
+}
+
+// Tests to check the accessors extracting the parsed instance.
+#[test]
+fn test_parse_result_accessors() {
+    let totp = parse_otpauth_uri("otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP").unwrap();
+    assert!(totp.is_totp());
+    assert!(!totp.is_hotp());
+    assert_eq!(totp.as_totp().unwrap().get_period(), 30);
+    assert!(totp.as_hotp().is_none());
+    assert_eq!(totp.into_totp().unwrap().get_account(), Some("john"));
+
+    let hotp =
+        parse_otpauth_uri("otpauth://hotp/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=7").unwrap();
+    assert!(hotp.is_hotp());
+    assert!(!hotp.is_totp());
+    assert!(hotp.as_totp().is_none());
+    assert_eq!(hotp.as_hotp().unwrap().1, 7);
+    let (parsed, counter) = hotp.into_hotp().unwrap();
+    assert_eq!(parsed.get_digits(), 6);
+    assert_eq!(counter, 7);
+}
+
+// Tests to check the scheme and OTP type are case-insensitive.
+#[test]
+fn test_otpauth_parse_uppercase() {
+    let parse_totp = |uri| match parse_otpauth_uri(uri).unwrap() {
+        ParseResult::TOTP(totp) => totp,
+        ParseResult::HOTP(_, _) => panic!("Expected a TOTP instance"),
+    };
+    let canonical = parse_totp("otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP&issuer=ACME");
+    let uppercase = parse_totp("OTPAUTH://TOTP/ACME:john?secret=JBSWY3DPEHPK3PXP&issuer=ACME");
+    assert_eq!(canonical, uppercase);
+    assert_eq!(uppercase.get_issuer(), Some("ACME"));
+
+    let res = parse_otpauth_uri("OtpAuth://Hotp/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=3");
+    assert!(matches!(res, Ok(ParseResult::HOTP(_, 3))));
+}
+
+// Tests to check the type of a URI is read without parsing the rest of it.
+#[test]
+fn test_otp_type() {
+    let totp = otp_type("otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP");
+    assert_eq!(totp.unwrap(), OtpType::Totp);
+    let hotp = otp_type("OTPAUTH://HOTP/ACME:john?secret=JBSWY3DPEHPK3PXP&counter=3");
+    assert_eq!(hotp.unwrap(), OtpType::Hotp);
+
+    // The secret and parameters aren't validated
+    assert_eq!(otp_type("otpauth://hotp/ACME?secret=1!").unwrap(), OtpType::Hotp);
+    assert_eq!(otp_type("otpauth://totp/ACME").unwrap(), OtpType::Totp);
+
+    assert!(matches!(
+        otp_type("auth://totp/ACME?secret=JBSWY3DPEHPK3PXP"),
+        Err(ParseError::WrongScheme(_))
+    ));
+    assert!(matches!(
+        otp_type("otpauth://motp/ACME?secret=JBSWY3DPEHPK3PXP"),
+        Err(ParseError::UnknownOtpType(x)) if x == "motp"
+    ));
+    assert!(matches!(otp_type(""), Err(ParseError::UriParseError(_))));
+}
+
+#[test]
+fn test_totp_to_uri_round_trip_sha384() {
+    let totp = TOTP::new(b"12345678901234567890", MacDigest::SHA384, 8, 30);
+    let uri = totp.to_uri("ACME:john", None);
+    assert!(uri.contains("algorithm=SHA384"));
+    let parsed = parse_otpauth_uri(&uri).unwrap().into_totp().unwrap();
+    assert_eq!(parsed.get_digest(), MacDigest::SHA384);
+}
+
+// Tests to check digest names are parsed case-insensitively.
+#[test]
+fn test_mac_digest_from_str() {
+    assert_eq!("SHA1".parse::<MacDigest>().unwrap(), MacDigest::SHA1);
+    assert_eq!("sha1".parse::<MacDigest>().unwrap(), MacDigest::SHA1);
+    assert_eq!("Sha-256".parse::<MacDigest>().unwrap(), MacDigest::SHA256);
+    assert_eq!("sha512".parse::<MacDigest>().unwrap(), MacDigest::SHA512);
+    assert_eq!("SHA224".parse::<MacDigest>().unwrap(), MacDigest::SHA224);
+    assert_eq!("sha-384".parse::<MacDigest>().unwrap(), MacDigest::SHA384);
+    assert!(matches!(
+        "MD5".parse::<MacDigest>(),
+        Err(ParseError::UnknownAlgorithm(_))
+    ));
+}
+
+#[test]
+fn test_otpauth_parse_lowercase_algorithm() {
+    for (name, digest) in [("sha256", MacDigest::SHA256), ("SHA-512", MacDigest::SHA512)] {
+        let uri = format!("otpauth://totp/ACME:john?secret=JBSWY3DPEHPK3PXP&algorithm={name}");
+        match parse_otpauth_uri(&uri).unwrap() {
+            ParseResult::TOTP(totp) => assert_eq!(totp.get_digest(), digest),
+            ParseResult::HOTP(_, _) => unreachable!(),
+        }
+    }
+}
+
+// Tests to check digest names are displayed and parsed back.
+#[test]
+fn test_mac_digest_display() {
+    for digest in [
+        MacDigest::SHA1,
+        MacDigest::SHA256,
+        MacDigest::SHA512,
+        MacDigest::SHA224,
+        MacDigest::SHA384,
+    ] {
+        assert_eq!(digest.to_string(), digest.name());
+        assert_eq!(digest.name().parse::<MacDigest>().unwrap(), digest);
+    }
+    assert_eq!(MacDigest::SHA256.to_string(), "SHA256");
+
+    let totp = TOTP::new(b"secret", MacDigest::SHA512, 6, 30);
+    assert_eq!(totp.get_algorithm_name(), "SHA512");
+    assert_eq!(TOTP::default_from_secret(b"secret").get_algorithm_name(), "SHA1");
+}
+
+// Tests to check byte comparisons don't stop at the first mismatch.
+#[test]
+fn test_constant_time_eq_pairs() {
+    let expected = b"287082";
+    for candidate in [b"287082", b"987082", b"287089"] {
+        let mut visited = 0;
+        let pairs = expected
+            .iter()
+            .copied()
+            .zip(candidate.iter().copied())
+            .inspect(|_| visited += 1);
+        assert_eq!(constant_time_eq_pairs(pairs), candidate == expected);
+        // A first-byte and a last-byte mismatch both compare every byte
+        assert_eq!(visited, expected.len());
+    }
+}
+
+// Tests to check encoding names are displayed.
+#[test]
+fn test_otp_encoding_display() {
+    use xotp::util::OtpEncoding;
+
+    assert_eq!(OtpEncoding::Decimal.to_string(), "Decimal");
+    assert_eq!(OtpEncoding::Steam.to_string(), OtpEncoding::Steam.name());
+    assert_eq!(OtpEncoding::Steam.name(), "Steam");
+}
+
+// Tests to check the digest size parameters.
+#[test]
+fn test_mac_digest_sizes() {
+    assert_eq!(MacDigest::SHA1.output_bytes(), 20);
+    assert_eq!(MacDigest::SHA256.output_bytes(), 32);
+    assert_eq!(MacDigest::SHA512.output_bytes(), 64);
+    assert_eq!(MacDigest::SHA1.block_size(), 64);
+    assert_eq!(MacDigest::SHA256.block_size(), 64);
+    assert_eq!(MacDigest::SHA512.block_size(), 128);
+    assert_eq!(MacDigest::SHA224.output_bytes(), 28);
+    assert_eq!(MacDigest::SHA384.output_bytes(), 48);
+    assert_eq!(MacDigest::SHA224.block_size(), 64);
+    assert_eq!(MacDigest::SHA384.block_size(), 128);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_mac_digest_sizes_sha3() {
+    assert_eq!(MacDigest::SHA3_256.output_bytes(), 32);
+    assert_eq!(MacDigest::SHA3_512.output_bytes(), 64);
+    assert_eq!(MacDigest::SHA3_256.block_size(), 136);
+    assert_eq!(MacDigest::SHA3_512.block_size(), 72);
+}
+
+// Tests to check codes generated from a borrowed secret match the instances.
+// Tests to check hashing with a digest selected by name
+#[test]
+fn test_hash_with_name() {
+    let secret = b"12345678901234567890";
+    let hash = hash_with_name(&0_u64.to_be_bytes(), secret, "sha-1").unwrap();
+    assert_eq!(hash, HOTP::new(secret, 6).hmac_for_counter(0));
+    // RFC4226 Appendix D, count 0
+    assert_eq!(hash[..4], [0xcc, 0x93, 0xcf, 0x18]);
+
+    let sha256 = hash_with_name(b"message", b"secret", "SHA256").unwrap();
+    assert_eq!(sha256.len(), 32);
+    assert_eq!(hash_with_name(b"message", b"secret", "sha512").unwrap().len(), 64);
+
+    assert!(matches!(
+        hash_with_name(b"message", b"secret", "MD5"),
+        Err(ParseError::UnknownAlgorithm(x)) if x == "MD5"
+    ));
+}
+
+// Tests to check truncation against the worked example of RFC4226 Section 5.4
+#[test]
+fn test_dynamic_truncate() {
+    let hash = [
+        0x1f, 0x86, 0x98, 0x69, 0x0e, 0x02, 0xca, 0x16, 0x61, 0x85, 0x50, 0xef, 0x7f, 0x19, 0xda,
+        0x8e, 0x94, 0x5b, 0x55, 0x5a,
+    ];
+    assert_eq!(dynamic_truncate(&hash, 6).as_u32(), 872921);
+    assert_eq!(dynamic_truncate(&hash, 10).as_u32(), 0x50ef7f19);
+    assert_eq!(dynamic_truncate(&hash, 8).as_string(), "57872921");
+}
+
+#[test]
+fn test_borrowed_secret_codes() {
+    let secret = b"12345678901234567890";
+    let totp = TOTP::new(secret, MacDigest::SHA1, 8, 30);
+    assert_eq!(totp_code(secret, MacDigest::SHA1, 8, 30, 59), totp.get_otp(59));
+    assert_eq!(totp_code(secret, MacDigest::SHA1, 8, 30, 59).as_u32(), 94287082);
+    assert_eq!(
+        totp_code(secret, MacDigest::SHA1, 8, 30, 1111111109).seconds_remaining(),
+        Some(1)
+    );
+
+    // A custom start time is applied by offsetting the time
+    let started = TOTP::new(secret, MacDigest::SHA1, 8, 30).with_time_start(100);
+    assert_eq!(totp_code(secret, MacDigest::SHA1, 8, 30, 159 - 100), started.get_otp(159));
+
+    let hotp = HOTP::new(secret, 6);
+    assert_eq!(hotp_code(secret, 6, 1), hotp.get_otp(1));
+    assert_eq!(hotp_code(secret, 6, 0).as_u32(), 755224);
+}
+
+// Tests to check batch generation matches generating each code on its own.
+#[test]
+fn test_generate_batch() {
+    let first = TOTP::new(b"12345678901234567890", MacDigest::SHA1, 8, 30);
+    let second = TOTP::new(b"another secret value", MacDigest::SHA256, 6, 60);
+    let items = [
+        (&first, 59),
+        (&first, 1111111109),
+        (&second, 59),
+        (&first, 2000000000),
+    ];
+    let results = generate_batch(&items);
+    assert_eq!(results.len(), items.len());
+    for ((totp, time), result) in items.iter().zip(&results) {
+        assert_eq!(*result, totp.get_otp(*time));
+    }
+    assert_eq!(results[0].as_u32(), 94287082);
+    assert!(generate_batch(&[]).is_empty());
+}
+
+// Tests to check base32 encoding and decoding.
+#[test]
+fn test_base32_round_trip() {
+    let encoded = base32_encode(b"12345678901234567890");
+    assert_eq!(encoded, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    assert_eq!(
+        base32_decode(&encoded).unwrap(),
+        b"12345678901234567890".to_vec()
+    );
+}
+
+#[test]
+fn test_base32_encode_unpadded() {
+    assert_eq!(base32_encode(b"secret"), "ONSWG4TFOQ");
+}
+
+#[test]
+fn test_base32_decode_padding_and_whitespace() {
+    assert_eq!(
+        base32_decode("ONSWG4TFOQ======").unwrap(),
+        b"secret".to_vec()
+    );
+    assert_eq!(
+        base32_decode("  ONSWG4TFOQ====\n").unwrap(),
+        b"secret".to_vec()
+    );
+    assert!(base32_decode("ONSWG4TFOQ!").is_none());
+}
+
+#[test]
+fn test_padded_secret_constructor() {
+    let totp = TOTP::try_default_from_base32("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ====").unwrap();
+    assert_eq!(totp.get_secret(), b"12345678901234567890");
+}
+
+#[test]
+fn test_base32_decode_normalizes_input() {
+    let expected = b"12345678901234567890".to_vec();
+    assert_eq!(
+        base32_decode("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap(),
+        expected
+    );
+    assert_eq!(
+        base32_decode("GEZD-GNBV-GY3T-QOJQ-GEZD-GNBV-GY3T-QOJQ").unwrap(),
+        expected
+    );
+}
+
+// Tests to check decoding with the alternative base32 alphabets.
+#[test]
+fn test_base32_decode_with_alphabet() {
+    assert_eq!(
+        base32_decode_with_alphabet("64P36D1L6ORJGE9G", Base32Alphabet::Rfc4648Hex),
+        Some(b"1234567890".to_vec())
+    );
+    assert_eq!(
+        base32_decode_with_alphabet("64p3-6d1l-6orj-ge9g===", Base32Alphabet::Rfc4648Hex),
+        Some(b"1234567890".to_vec())
+    );
+    assert_eq!(
+        base32_decode_with_alphabet("GEZDGNBVGY3TQOJQ", Base32Alphabet::default()),
+        base32_decode("GEZDGNBVGY3TQOJQ")
+    );
+    assert_eq!(base32_decode_with_alphabet("WXYZ", Base32Alphabet::Rfc4648Hex), None);
+}
+
+// Tests to check secrets are validated without constructing a generator
+#[test]
+fn test_validate_base32_secret() {
+    assert_eq!(validate_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap(), 20);
+    assert_eq!(validate_base32_secret("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap(), 20);
+    assert!(matches!(
+        validate_base32_secret("JBSWY3DPEHPK3PXP"),
+        Err(ParseError::SecretTooShort(10))
+    ));
+    assert!(matches!(validate_base32_secret(" "), Err(ParseError::EmptySecret)));
+    assert!(matches!(
+        validate_base32_secret("GEZDGNBVGY3TQOJQ1!"),
+        Err(ParseError::SecretParsingError(_))
+    ));
+}
+
+// Tests to check parsing Google Authenticator migration URIs.
+#[cfg(feature = "migration")]
+static MIGRATION_URI: &str = "otpauth-migration://offline?data=Cj8KFDEyMzQ1Njc4OTAxMjM0NTY3ODkwEhhBQ01FIENvOmpvaG5AZXhhbXBsZS5jb20aB0FDTUUgQ28gAigCMAIKMQoUMTIzNDU2Nzg5MDEyMzQ1Njc4OTASEWFsaWNlQGV4YW1wbGUuY29tIAEoATABOAUQARgBIAAouWA%3D";
+
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_parse() {
+    let results = parse_migration_uri(MIGRATION_URI).unwrap();
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0].issuer(), Some("ACME Co"));
+    assert_eq!(results[0].account(), Some("john@example.com"));
+    match &results[0] {
+        ParseResult::TOTP(totp) => {
+            assert_eq!(totp.get_digest(), MacDigest::SHA256);
+            assert_eq!(totp.get_digits(), 8);
+            assert_eq!(totp.get_period(), 30);
+            assert_eq!(totp.get_secret(), b"12345678901234567890");
+        }
+        ParseResult::HOTP(_, _) => unreachable!(),
+    }
+
+    assert_eq!(results[1].issuer(), None);
+    assert_eq!(results[1].account(), Some("alice@example.com"));
+    match &results[1] {
+        ParseResult::HOTP(hotp, counter) => {
+            assert_eq!(*counter, 5);
+            assert_eq!(hotp.get_digits(), 6);
+            assert_eq!(hotp.get_otp(1).as_u32(), 287082);
+        }
+        ParseResult::TOTP(_) => unreachable!(),
+    }
+}
+
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_parse_unencoded_plus() {
+    let uri = MIGRATION_URI.replace("%3D", "");
+    assert_eq!(parse_migration_uri(&uri).unwrap().len(), 2);
+}
+
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_parse_uppercase() {
+    let uri = MIGRATION_URI.replace("otpauth-migration://offline", "OTPAUTH-MIGRATION://OFFLINE");
+    assert_eq!(parse_migration_uri(&uri).unwrap().len(), 2);
+}
+
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_parse_errors() {
+    assert!(matches!(
+        parse_migration_uri("otpauth://offline?data=AAAA"),
+        Err(ParseError::WrongScheme(_))
+    ));
+    assert!(matches!(
+        parse_migration_uri("otpauth-migration://offline"),
+        Err(ParseError::MissingMigrationData)
+    ));
+    assert!(matches!(
+        parse_migration_uri("otpauth-migration://offline?data=not%20base64!"),
+        Err(ParseError::InvalidMigrationData(_))
+    ));
+    // A truncated OtpParameters message
+    assert!(matches!(
+        parse_migration_uri("otpauth-migration://offline?data=Cj8KFA"),
+        Err(ParseError::InvalidMigrationData(_))
+    ));
+}
+
+// Tests to check building Google Authenticator migration URIs.
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_round_trip() {
+    let items = parse_migration_uri(MIGRATION_URI).unwrap();
+    let uri = build_migration_uri(&items).unwrap();
+    assert!(uri.starts_with("otpauth-migration://offline?data="));
+
+    let round_trip = parse_migration_uri(&uri).unwrap();
+    assert_eq!(round_trip.len(), 2);
+    for (item, parsed) in items.iter().zip(&round_trip) {
+        assert_eq!(item.issuer(), parsed.issuer());
+        assert_eq!(item.account(), parsed.account());
+        match (item, parsed) {
+            (ParseResult::TOTP(a), ParseResult::TOTP(b)) => {
+                assert_eq!(a.get_digest(), b.get_digest());
+                assert_eq!(a.get_otp(1111111109), b.get_otp(1111111109));
+            }
+            (ParseResult::HOTP(a, x), ParseResult::HOTP(b, y)) => {
+                assert_eq!(x, y);
+                assert_eq!(a.get_otp(*x), b.get_otp(*y));
+            }
+            _ => panic!("OTP type changed in the round trip"),
+        }
+    }
+}
+
+#[cfg(feature = "migration")]
+#[test]
+fn test_migration_build_unsupported() {
+    let seven_digits = ParseResult::TOTP(TOTP::new(b"secret", MacDigest::SHA1, 7, 30));
+    assert!(matches!(
+        build_migration_uri(&[seven_digits]),
+        Err(ParseError::WrongDigitNumber {
+            reason: ValueError::Unsupported,
+            ..
+        })
+    ));
+
+    let sha224 = ParseResult::TOTP(TOTP::new(b"secret", MacDigest::SHA224, 6, 30));
+    assert!(matches!(
+        build_migration_uri(&[sha224]),
+        Err(ParseError::UnknownAlgorithm(x)) if x == "SHA224"
+    ));
+
+    let custom_period = ParseResult::TOTP(TOTP::new(b"secret", MacDigest::SHA1, 6, 60));
+    assert!(matches!(
+        build_migration_uri(&[custom_period]),
+        Err(ParseError::InvalidPeriod { .. })
+    ));
+
+    let time_start =
+        ParseResult::TOTP(TOTP::new(b"secret", MacDigest::SHA1, 6, 30).with_time_start(10));
+    assert!(matches!(
+        build_migration_uri(&[time_start]),
+        Err(ParseError::InvalidTimeStart(_))
+    ));
+
+    let steam = ParseResult::TOTP(
+        TOTP::new(b"secret", MacDigest::SHA1, 6, 30).with_encoding(OtpEncoding::Steam),
+    );
+    assert!(matches!(
+        build_migration_uri(&[steam]),
+        Err(ParseError::UnsupportedEncoding(x)) if x == "Steam"
+    ));
+
+    let hotp = ParseResult::HOTP(HOTP::new(b"secret", 6), 3);
+    assert!(build_migration_uri(&[hotp]).is_ok());
+}